@@ -7,9 +7,10 @@ export default class Answers extends Component {
         <div>
         <h3>Answers thus far</h3>
         <ul>
-        {this.props.answers.map(([answer, answerIndex], index) => {
-          let emoji = (answerIndex !== undefined ? "❤️" : "🤔");
-          return (<li key={`answer-${index}`}>{emoji} {answer}</li>);
+        {this.props.answers.map((answer, index) => {
+          let emoji = (answer.correct ? "❤️" : "🤔");
+          let note = (answer.feedback ? ` (${answer.feedback})` : "");
+          return (<li key={`answer-${index}`}>{emoji} {answer.text}{note}</li>);
         })}
         </ul>
           </div>
@@ -6,20 +6,24 @@ export default class Card {
     this.meanings = [];
     this.conjugations = [];
     this.partOfSpeech = null;
+    this.tags = [];
   }
 
   static async fetch(cardUuid) {
     let cardData = await fetch(`${HOST}/card/${cardUuid}`).then(r => r.json());
     let card = new Card(cardUuid);
-    for (let line of cardData.lines) {
+    for (let [index, line] of cardData.lines.entries()) {
       if (line.kind === "PartOfSpeech") {
         card.partOfSpeech = line.text;
       } else if (line.kind.Meaning !== undefined) {
         card.meanings.push({
           language: line.kind.Meaning,
           text: line.text,
+          romanized: cardData.romanized[index],
         });
       } else if (line.kind === "Comment") {
+      } else if (line.kind === "Tags") {
+        card.tags.push(...line.text.split(/\s+/).filter(t => t.length > 0));
       } else if (line.kind === "Aoristos") {
         card.conjugations.push({
           kind: line.kind,
@@ -32,6 +36,13 @@ export default class Card {
     return card;
   }
 
+  // Meanings along with their romanizations, if the server sent any.
+  describedMeaningsIn(inLanguage) {
+    return this.meanings
+      .filter(m => m.language === inLanguage)
+      .map(describeMeaning);
+  }
+
   meaningsIn(inLanguage) {
     return this.meanings
       .filter(m => m.language === inLanguage)
@@ -39,3 +50,6 @@ export default class Card {
   }
 }
 
+export function describeMeaning(meaning) {
+  return meaning.romanized ? `${meaning.text} (${meaning.romanized})` : meaning.text;
+}
@@ -2,6 +2,10 @@ import React, { Component } from 'react';
 
 export default class MissingAnswers extends Component {
   render() {
+    if (this.props.missingAnswers.length === 0) {
+      return null;
+    }
+
     return (
         <div>
         <h3>Missing Answers</h3>
@@ -33,19 +33,28 @@ export default class QuizComponent extends Component {
     // Current card we are showing to the user
     card: null,
 
-    // Answers the user has given thus far; a list of [answer, index] tuples
-    // where:
+    // Answers the user has given thus far, as graded by the server; a
+    // list of objects with fields:
     //
-    // - answer is the string
-    // - index is the index of the answer from the list of expected answers
-    //   or undefined
+    // - text: the answer the user gave
+    // - correct: true if it was accepted
+    // - expected: the expected answer that it matched best, or null
+    // - feedback: a note from the server if the answer was close
+    //   (e.g., "did you mean ...?") or null
     answers: [],
 
-    // If this is non-empty, then the user has given all their answers,
-    // but there were some mistakes. We need to ask them how they feel
-    // about their work...did they know it...yes/no/almost?
+    // True if some answer was close but not quite right, in which case
+    // we suggest "almost" as the result.
+    suggestAlmost: false,
+
+    // Answers we expected but did not get from the user.
     missingAnswers: [],
 
+    // If true, then the user has given all their answers, but there
+    // were some mistakes (or near misses). We need to ask them how
+    // they feel about their work...did they know it...yes/no/almost?
+    grading: false,
+
     // List of pending transliteration promises. We append each
     // transliteration request to this list as the user types. When
     // they hit enter, we wait for them all to complete (most will
@@ -98,7 +107,9 @@ export default class QuizComponent extends Component {
     this.setState({
       card: null,
       answers: [],
+      suggestAlmost: false,
       missingAnswers: [],
+      grading: false,
       pendingTransliterations: [],
     });
   }
@@ -197,14 +208,14 @@ export default class QuizComponent extends Component {
         <div className="col-xs-12">
         <h1>Translate to {toLanguage}</h1>
         <ul>
-        {card.meaningsIn(fromLanguage).map((meaning, index) => (
+        {card.describedMeaningsIn(fromLanguage).map((meaning, index) => (
             <li key={`meaning-${index}`}> {meaning} </li>
         ))}
          </ul>
 
         <Answers answers={this.state.answers}/>
 
-      {this.state.missingAnswers.length > 0 ? (
+      {this.state.grading ? (
           <div onKeyPress={(e) => log.debug(e.key)}>
           <MissingAnswers missingAnswers={this.state.missingAnswers}/>
           <h3>Did you know it?</h3>
@@ -212,7 +223,7 @@ export default class QuizComponent extends Component {
           <ul>
           <li> <button onClick={answerYes}>Yes</button> </li>
           <li> <button onClick={answerNo}>No</button> </li>
-          <li> <button onClick={answerAlmost}>Almost...</button> </li>
+          <li> <button onClick={answerAlmost}>Almost...{this.state.suggestAlmost ? " (suggested)" : ""}</button> </li>
           </ul>
           </form>
           </div>
@@ -268,29 +279,26 @@ export default class QuizComponent extends Component {
       return this.determineGrade();
     }
 
-    let answerIndex = await this.answerIndex(answer);
-
-    // Check if we already *had* this answer, or something equivalent
-    // to it.  If so, just clear it and reset back.
-    for (let [oldAnswer, oldAnswerIndex] of this.state.answers) {
-      if (answer === oldAnswer || (oldAnswerIndex !== undefined && oldAnswerIndex === answerIndex)) {
-        return this.expectMoreAnswers();
-      }
+    // Check if we already *had* this answer. If so, just clear it and
+    // reset back.
+    if (this.state.answers.some(a => a.text === answer)) {
+      return this.expectMoreAnswers();
     }
 
-    // Otherwise, figure out if it is correct and add it to our list of answers.
+    // Otherwise, have the server grade it along with the others.
+    let report = await this.gradeAnswers(this.state.answers.map(a => a.text).concat([answer]));
     this.setState({
-      answers: this.state.answers.concat([[answer, answerIndex]]),
+      answers: report.answers,
     });
 
     // Now, if we still expect more answers, wait for them.
     let expectedAnswers = this.expectedAnswers();
-    if (this.state.answers.length < expectedAnswers.length) {
+    if (report.answers.length < expectedAnswers.length) {
       return this.expectMoreAnswers();
     }
 
     // Otherwise...
-    await this.determineGrade();
+    await this.determineGrade(report);
   }
 
   expectMoreAnswers() {
@@ -302,61 +310,52 @@ export default class QuizComponent extends Component {
     return;
   }
 
-  async determineGrade() {
+  // Decides how the user did, given the server's grading `report`
+  // of the answers thus far (fetched if not supplied).
+  async determineGrade(report) {
     log.debug(`determineGrade()`);
 
-    let expectedAnswers = this.expectedAnswers();
-    let missingAnswers = expectedAnswers.slice(0); // make a local clone
-    log.debug(`missingAnswers: ${missingAnswers}`);
-    for (let [answer, answerIndex] of this.state.answers) {
-      log.debug(`answer ${answer} matched at index ${answerIndex}`);
-      if (answerIndex !== undefined) {
-        missingAnswers[answerIndex] = null;
-      }
+    if (report === undefined) {
+      report = await this.gradeAnswers(this.state.answers.map(a => a.text));
     }
-    log.debug(`missingAnswers: ${missingAnswers}`);
-    missingAnswers = missingAnswers.filter(a => a != null);
-    log.debug(`missingAnswers: ${missingAnswers}`);
+    log.debug(`determineGrade: report=${JSON.stringify(report)}`);
 
-    if (missingAnswers.length === 0) {
+    if (report.suggested === "Yes") {
       await this.submitResult("yes");
     } else {
-      this.setState({missingAnswers});
+      this.setState({
+        missingAnswers: report.missing,
+        suggestAlmost: report.suggested === "Almost",
+        grading: true,
+      });
     }
 
     return;
   }
 
-  async checkAnswer(expected, user) {
-    let uri = `${HOST}/check_answer/` +
-        `${encodeURIComponent(expected)}/` +
-        `${encodeURIComponent(user)}`;
-    log.debug(`checkAnswer: uri=${uri}`);
-    return await fetch(uri).then(r => r.json());
-  }
-
-  // Returns the index of answer in the list of expected answers, or else undefined.
-  async answerIndex(answer) {
-    let expectedAnswers = this.expectedAnswers();
-    for (let i = 0; i < expectedAnswers.length; i++) {
-      let result = await this.checkAnswer(expectedAnswers[i], answer);
-      log.debug(`checkAnswer returned ${result}`);
-      if (result) {
-        return i;
-      }
-    }
-    return undefined;
+  // Asks the server to grade the given answers to the current
+  // question, just as `mathema quiz` would. Returns an object with
+  // the graded `answers`, the `missing` expected answers, and the
+  // `suggested` result (if any).
+  async gradeAnswers(answers) {
+    let question = this.state.questions[this.state.index];
+    let request = {
+      uuid: question.uuid,
+      question_kind: question.questionKind,
+      answers,
+    };
+    return await post(`${HOST}/grade_answers`, request).then(r => r.json());
   }
 
   async keydownEvent(event) {
-    if (this.state.missingAnswers.length > 0) {
+    if (this.state.grading) {
       log.debug(`received shkeydownEvent: ${event.code}`);
       this.loggedKeyDown = event.code;
     }
   }
 
   async keyupEvent(event) {
-    if (this.state.missingAnswers.length > 0) {
+    if (this.state.grading) {
       // waiting for a yes, no, almost...
       log.debug(`received keyupEvent: ${event.code}`);
 
@@ -1,5 +1,5 @@
 import React, { Component } from 'react';
-import Card from "./Card";
+import Card, { describeMeaning } from "./Card";
 import { HOST } from "./Constants";
 
 export default class ViewCards extends Component {
@@ -22,7 +22,7 @@ export default class ViewCards extends Component {
                 <h5 className="card-title">{card.uuid}</h5>
               <h6 className="card-subtitle mb-2 text-muted">
               {card.meanings.map(m => (
-                  <span>{m.language}: {m.text}</span>
+                  <span>{m.language}: {describeMeaning(m)}</span>
               ))}
                 </h6>
               </div>
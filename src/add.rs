@@ -48,14 +48,16 @@ crate fn add(options: &MathemaOptions, file: &str) -> Fallible<()> {
         println!("`{}` already found in database.", repo_path.display());
     }
 
+    let new_cards = if uuids_assigned == 1 {
+        "1 new card".to_string()
+    } else {
+        format!("{} new cards", uuids_assigned)
+    };
+
     // Finally, write everything back out.
-    repo.write_database()?;
+    repo.write_database(&format!("add: {} ({})", repo_path.display(), new_cards))?;
 
-    if uuids_assigned == 1 {
-        println!("1 new card found.");
-    } else {
-        println!("{} new cards found.", uuids_assigned);
-    }
+    println!("{} found.", new_cards);
 
     Ok(())
 }
@@ -25,6 +25,7 @@ crate enum LineKind {
     Meaning(Language),
     PartOfSpeech,
     Aoristos,
+    Tags,
 }
 
 impl Card {
@@ -33,6 +34,13 @@ impl Card {
         self.lines_with_kind(kind)
     }
 
+    /// Tags are written as whitespace-separated words on a `tags` line;
+    /// a card may have any number of such lines.
+    crate fn tags(&self) -> impl Iterator<Item = &str> + '_ {
+        self.lines_with_kind(LineKind::Tags)
+            .flat_map(|text| text.split_whitespace())
+    }
+
     crate fn is_comment_card(&self) -> bool {
         self.lines.iter().all(|l| l.kind == LineKind::Comment)
     }
@@ -112,6 +120,11 @@ fn parse_card(source_file: &Path, parser: &mut LineParser) -> Fallible<Card> {
                     kind: LineKind::Aoristos,
                     text: remainder.to_string(),
                 });
+            } else if word0 == "tags" {
+                card.lines.push(CardLine {
+                    kind: LineKind::Tags,
+                    text: remainder.to_string(),
+                });
             } else if let Ok(language) = Language::from_str(word0) {
                 let kind = LineKind::Meaning(language);
                 let text = language.transliterate(remainder);
@@ -167,6 +180,7 @@ impl fmt::Display for LineKind {
             LineKind::Meaning(lang) => write!(fmt, "{}", lang.abbreviation()),
             LineKind::PartOfSpeech => write!(fmt, "pos"),
             LineKind::Aoristos => write!(fmt, "αόριστος"),
+            LineKind::Tags => write!(fmt, "tags"),
         }
     }
 }
@@ -0,0 +1,239 @@
+//! Settings, read from two files: per-deck settings from
+//! `mathema-config.ron` in the deck directory, and per-user settings
+//! from `mathema/config.ron` in the user's configuration directory
+//! (e.g., `~/.config`), or wherever `MATHEMA_USER_CONFIG` points. The
+//! files are optional, and so is every setting in them.
+
+use crate::language::{self, LanguageId, TransliterationTable};
+use crate::prelude::*;
+use crate::storage::StorageKind;
+
+crate const RELATIVE_CONFIG_PATH: &str = "mathema-config.ron";
+
+/// Where to find the user configuration, if anywhere.
+crate fn user_config_path() -> Option<PathBuf> {
+    match env::var_os("MATHEMA_USER_CONFIG") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => dirs::config_dir().map(|dir| dir.join("mathema").join("config.ron")),
+    }
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+crate struct DeckConfig {
+    /// Whether answers in a given language must include the article,
+    /// e.g. `το μάθημα` rather than just `μάθημα`. Languages not listed
+    /// here require it. Keyed by id, as the deck's own languages are
+    /// only defined once the config is loaded.
+    crate articles: HashMap<LanguageId, ArticleRule>,
+
+    /// Languages besides the built-in ones.
+    crate languages: Vec<LanguageConfig>,
+
+    /// Where the deck's history is kept: in git (the default), or
+    /// nowhere but the directory itself.
+    crate storage: StorageKind,
+}
+
+/// A language defined by the deck.
+#[derive(Clone, Serialize, Deserialize)]
+crate struct LanguageConfig {
+    /// The name we store in the database, e.g. `Ukrainian`. Don't
+    /// change it, or mathema sets aside the history of cards in it
+    /// (see `journal::UnknownAnswer`).
+    crate id: String,
+
+    /// The code used in card files and on the command line, e.g. `uk`.
+    crate code: String,
+
+    /// The name of the language in the language itself.
+    crate name: String,
+
+    /// How to type the language: a table from Latin letters, or groups
+    /// of them, to what they stand for, e.g. `{ "zh": "ж" }`. Without
+    /// one, the language is typed as is.
+    #[serde(default)]
+    crate transliteration: HashMap<String, String>,
+
+    /// Articles that may start a noun phrase in this language.
+    #[serde(default)]
+    crate articles: Vec<String>,
+
+    /// How to normalize responses before checking them.
+    #[serde(default)]
+    crate normalization: Vec<Normalization>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+crate enum ArticleRule {
+    Required,
+    Optional,
+}
+
+impl DeckConfig {
+    crate fn load_from(reader: impl io::Read) -> Fallible<Self> {
+        let config = ::ron::de::from_reader(reader)?;
+        Ok(config)
+    }
+
+    /// Makes the deck's languages known, so that cards and the
+    /// database can use them.
+    crate fn define_languages(&self) -> Fallible<()> {
+        for language in &self.languages {
+            language.define()?;
+        }
+        for LanguageId(id) in self.articles.keys() {
+            if Language::from_id(id).is_none() {
+                throw!(MathemaErrorKind::UnrecognizedLanguage { text: id.clone() });
+            }
+        }
+        Ok(())
+    }
+
+    /// How leniently to check answers given in `language`.
+    crate fn answer_syntax(&self, language: Language) -> AnswerSyntax {
+        let optional_articles = match self.articles.get(language.id()) {
+            Some(ArticleRule::Optional) => language.articles(),
+            Some(ArticleRule::Required) | None => &[],
+        };
+        AnswerSyntax {
+            optional_articles,
+            normalization: language.normalization(),
+        }
+    }
+}
+
+impl LanguageConfig {
+    crate fn define(&self) -> Fallible<Language> {
+        let input_method = if self.transliteration.is_empty() {
+            InputMethod::Latin
+        } else {
+            let table = TransliterationTable::new(&self.transliteration);
+            InputMethod::Table(Box::leak(Box::new(table)))
+        };
+        let articles: Vec<&'static str> = self
+            .articles
+            .iter()
+            .map(|article| language::leak_str(article.clone()))
+            .collect();
+
+        Language::define(LanguageDefinition {
+            id: language::leak_str(self.id.clone()),
+            code: language::leak_str(self.code.clone()),
+            name: language::leak_str(self.name.clone()),
+            input_method,
+            articles: Box::leak(articles.into_boxed_slice()),
+            normalization: Box::leak(self.normalization.clone().into_boxed_slice()),
+        })
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(default)]
+crate struct UserConfig {
+    /// How the user types answers in a given language. Languages not
+    /// listed here use mathema's own scheme. Keyed by id, as this file
+    /// is shared by every deck, and may list languages that only some
+    /// of them define.
+    crate transliteration: HashMap<LanguageId, TransliterationScheme>,
+
+    /// If some, show Greek romanized in this scheme alongside the
+    /// original.
+    crate romanization: Option<RomanizationScheme>,
+
+    /// If true, `mathema quiz` syncs with the `mathema` remote when it
+    /// is done.
+    crate sync_after_quiz: bool,
+}
+
+impl UserConfig {
+    crate fn load_from(reader: impl io::Read) -> Fallible<Self> {
+        let config = ::ron::de::from_reader(reader)?;
+        Ok(config)
+    }
+
+    crate fn transliteration(&self, language: Language) -> TransliterationScheme {
+        self.transliteration
+            .get(language.id())
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+#[test]
+fn load_config() {
+    let config = DeckConfig::load_from("(articles: { Greek: Optional })".as_bytes()).unwrap();
+    assert_eq!(
+        config.answer_syntax(Language::Greek).optional_articles,
+        Language::Greek.articles()
+    );
+
+    let config = DeckConfig::load_from("()".as_bytes()).unwrap();
+    assert!(config.articles.is_empty());
+    assert_eq!(config.storage, StorageKind::Git);
+
+    let config = DeckConfig::load_from("(storage: Directory)".as_bytes()).unwrap();
+    assert_eq!(config.storage, StorageKind::Directory);
+}
+
+#[test]
+fn load_user_config() {
+    let config =
+        UserConfig::load_from("(transliteration: { Greek: Greeklish })".as_bytes()).unwrap();
+    assert_eq!(
+        config.transliteration(Language::Greek),
+        TransliterationScheme::Greeklish
+    );
+    assert_eq!(
+        config.transliteration(Language::AncientGreek),
+        TransliterationScheme::Mathema
+    );
+    assert_eq!(config.romanization, None);
+    assert!(!config.sync_after_quiz);
+
+    // Other decks' languages are fine.
+    let config =
+        UserConfig::load_from("(transliteration: { Klingon: BetaCode })".as_bytes()).unwrap();
+    assert_eq!(
+        config.transliteration(Language::Greek),
+        TransliterationScheme::Mathema
+    );
+
+    let config = UserConfig::load_from("(romanization: Some(Iso843))".as_bytes()).unwrap();
+    assert_eq!(config.romanization, Some(RomanizationScheme::Iso843));
+}
+
+#[test]
+fn define_deck_language() {
+    let config = DeckConfig::load_from(
+        r#"(
+            languages: [
+                (
+                    id: "DeckLanguageTest",
+                    code: "dkt",
+                    name: "Українська",
+                    transliteration: { "k": "к", "i": "і", "t": "т", "zh": "ж", "z": "з", "h": "г" },
+                    normalization: [IgnoreStress],
+                ),
+            ],
+            articles: { DeckLanguageTest: Optional },
+        )"#
+        .as_bytes(),
+    )
+    .unwrap();
+    config.define_languages().unwrap();
+
+    let ukrainian = Language::from_str("dkt").unwrap();
+    assert_eq!(ukrainian.full_name(), "Українська");
+    assert_eq!(ukrainian.transliterate("kit"), "кіт");
+    assert_eq!(ukrainian.transliterate("zhk"), "жк");
+    assert_eq!(
+        config.answer_syntax(ukrainian).normalization,
+        &[Normalization::IgnoreStress]
+    );
+    assert!(config.articles.contains_key("DeckLanguageTest"));
+
+    // Articles only for languages that exist.
+    let config = DeckConfig::load_from("(articles: { Klingon: Optional })".as_bytes()).unwrap();
+    assert!(config.define_languages().is_err());
+}
@@ -0,0 +1,436 @@
+//! The journal is where we keep the user's answers: one JSON object per
+//! line, appended as soon as the answer is given, in one file per
+//! month (like `.mathema-history/2019-05.jsonl`). We never rewrite it,
+//! so each quiz makes a small diff, and two copies of the journal merge
+//! by simply taking the union of their lines (see `.gitattributes`).
+//! Deleting answers means appending a `Deletion` too, rather than
+//! removing lines that a merge would bring back. The records in
+//! `Database` are rebuilt from it when the repository is opened.
+
+use crate::prelude::*;
+use std::fs::OpenOptions;
+
+#[derive(Debug, Serialize, Deserialize)]
+crate struct JournalEntry {
+    crate uuid: Uuid,
+    crate question_kind: QuestionKind,
+    crate date: UtcDateTime,
+    crate result: QuestionResult,
+}
+
+impl JournalEntry {
+    crate fn record(&self) -> QuestionRecord {
+        QuestionRecord {
+            date: self.date,
+            result: self.result,
+        }
+    }
+}
+
+/// An answer as written in the journal, before we know whether the
+/// languages of its question are defined.
+#[derive(Serialize, Deserialize)]
+struct RawEntry {
+    uuid: Uuid,
+    question_kind: serde_json::Value,
+    date: UtcDateTime,
+    result: QuestionResult,
+}
+
+/// An answer to a question in a language that the deck doesn't define
+/// (any more). We can't ask it, but we keep it, so that `mathema fsck`
+/// can report it.
+#[derive(Clone, Debug)]
+crate struct UnknownAnswer {
+    crate uuid: Uuid,
+    crate question_kind: serde_json::Value,
+    crate date: UtcDateTime,
+}
+
+/// A note that every answer to the card `uuid` (or only those to
+/// `question_kind`, if there is one) up to `date` was deleted, as
+/// `mathema fsck --repair` does. The question kind is kept as written
+/// in the journal, so that it can name languages that aren't defined.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+crate struct Deletion {
+    crate uuid: Uuid,
+    crate question_kind: Option<serde_json::Value>,
+    crate date: UtcDateTime,
+}
+
+impl Deletion {
+    crate fn deletes(
+        &self,
+        uuid: Uuid,
+        question_kind: &serde_json::Value,
+        date: UtcDateTime,
+    ) -> bool {
+        uuid == self.uuid
+            && self
+                .question_kind
+                .as_ref()
+                .map_or(true, |kind| kind == question_kind)
+            && date <= self.date
+    }
+}
+
+/// A line of the journal.
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Line {
+    Answer(RawEntry),
+    Deletion { forget: Deletion },
+}
+
+/// What the journal holds, less what was deleted.
+#[derive(Default)]
+crate struct Journal {
+    crate entries: Vec<JournalEntry>,
+    crate unknown_answers: Vec<UnknownAnswer>,
+}
+
+/// The file (within the journal directory) that holds answers given
+/// on `date`.
+fn shard_name(date: UtcDateTime) -> String {
+    format!("{}.jsonl", date.format("%Y-%m"))
+}
+
+/// Appends `entries` to the journal in `directory`, and waits for them
+/// to reach the disk.
+crate fn append(directory: &Path, entries: &[JournalEntry]) -> Fallible<()> {
+    let mut shards: BTreeMap<String, String> = BTreeMap::new();
+    for entry in entries {
+        let lines = shards.entry(shard_name(entry.date)).or_default();
+        lines.push_str(&serde_json::to_string(entry)?);
+        lines.push('\n');
+    }
+    write_shards(directory, shards)
+}
+
+/// Appends `deletions` to the journal in `directory`, like `append`.
+crate fn append_deletions(directory: &Path, deletions: &[Deletion]) -> Fallible<()> {
+    let mut shards: BTreeMap<String, String> = BTreeMap::new();
+    for forget in deletions {
+        let lines = shards.entry(shard_name(forget.date)).or_default();
+        let forget = forget.clone();
+        lines.push_str(&serde_json::to_string(&Line::Deletion { forget })?);
+        lines.push('\n');
+    }
+    write_shards(directory, shards)
+}
+
+/// Appends the lines for each shard to it.
+fn write_shards(directory: &Path, shards: BTreeMap<String, String>) -> Fallible<()> {
+    if !shards.is_empty() {
+        fs::create_dir_all(directory)?;
+    }
+    for (shard_name, lines) in shards {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .read(true)
+            .append(true)
+            .open(directory.join(shard_name))?;
+        drop_partial_line(&mut file)?;
+        file.write_all(lines.as_bytes())?;
+        file.sync_data()?;
+    }
+    Ok(())
+}
+
+/// Cuts off a partial last line, from a crash in the middle of
+/// `append`. `read_shard` ignores it, but the next line appended after
+/// it would run into it, and the two would make a line we can't read.
+fn drop_partial_line(file: &mut File) -> Fallible<()> {
+    let mut text = vec![];
+    file.seek(io::SeekFrom::Start(0))?;
+    file.read_to_end(&mut text)?;
+    if text.last().map_or(false, |&byte| byte != b'\n') {
+        let complete = text
+            .iter()
+            .rposition(|&byte| byte == b'\n')
+            .map_or(0, |i| i + 1);
+        file.set_len(complete as u64)?;
+    }
+    Ok(())
+}
+
+/// Reads every answer in the journal in `directory`, if there is one,
+/// except those that were deleted.
+crate fn read(directory: &Path) -> Fallible<Journal> {
+    let mut journal = Journal::default();
+    if !directory.exists() {
+        return Ok(journal);
+    }
+
+    let mut shard_paths = vec![];
+    for entry in fs::read_dir(directory)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |e| e == "jsonl") {
+            shard_paths.push(path);
+        }
+    }
+    shard_paths.sort();
+
+    let mut raw_entries = vec![];
+    let mut deletions: Vec<Deletion> = vec![];
+    for shard_path in shard_paths {
+        for line in read_shard(&shard_path)? {
+            match line {
+                Line::Answer(raw_entry) => raw_entries.push(raw_entry),
+                Line::Deletion { forget } => deletions.push(forget),
+            }
+        }
+    }
+
+    for raw_entry in raw_entries {
+        let RawEntry {
+            uuid,
+            question_kind,
+            date,
+            result,
+        } = raw_entry;
+        if deletions
+            .iter()
+            .any(|deletion| deletion.deletes(uuid, &question_kind, date))
+        {
+            continue;
+        }
+
+        match serde_json::from_value(question_kind.clone()) {
+            Ok(question_kind) => journal.entries.push(JournalEntry {
+                uuid,
+                question_kind,
+                date,
+                result,
+            }),
+            Err(_) => journal.unknown_answers.push(UnknownAnswer {
+                uuid,
+                question_kind,
+                date,
+            }),
+        }
+    }
+    Ok(journal)
+}
+
+/// Reads one file of the journal. A partial last line, from a crash in
+/// the middle of `append`, is ignored.
+fn read_shard(path: &Path) -> Fallible<Vec<Line>> {
+    let text = fs::read_to_string(path)?;
+    let lines: Vec<&str> = text.lines().collect();
+    let complete_lines = if text.ends_with('\n') {
+        &lines[..]
+    } else {
+        &lines[..lines.len().saturating_sub(1)]
+    };
+
+    let mut parsed = vec![];
+    for line in complete_lines {
+        if !line.trim().is_empty() {
+            parsed.push(serde_json::from_str(line)?);
+        }
+    }
+    Ok(parsed)
+}
+
+/// Every answer recorded in `database`, oldest first.
+crate fn entries(database: &Database) -> Vec<JournalEntry> {
+    let mut entries: Vec<JournalEntry> = database
+        .user
+        .records
+        .iter()
+        .flat_map(|(&uuid, card_record)| {
+            card_record
+                .questions
+                .iter()
+                .flat_map(move |(&question_kind, records)| {
+                    records.iter().map(move |record| JournalEntry {
+                        uuid,
+                        question_kind,
+                        date: record.date,
+                        result: record.result,
+                    })
+                })
+        })
+        .collect();
+    entries.sort_by_key(|entry| (entry.date, entry.uuid, entry.question_kind));
+    entries
+}
+
+/// Adds the answers in `entries` to `database`, skipping any it already
+/// has (the same answer may be in the journal twice, after a merge).
+/// Returns the entries that were added.
+crate fn replay(database: &mut Database, entries: Vec<JournalEntry>) -> Vec<JournalEntry> {
+    entries
+        .into_iter()
+        .filter(|entry| {
+            database
+                .card_record_mut(entry.uuid)
+                .insert_question_record(entry.question_kind, entry.record())
+        })
+        .collect()
+}
+
+#[test]
+fn append_shards_by_month() {
+    let dir = tempdir::TempDir::new("mathema-journal").unwrap();
+    let entry = |month, result| JournalEntry {
+        uuid: Uuid::nil(),
+        question_kind: QuestionKind::Translate {
+            from: Language::Greek,
+            to: Language::English,
+        },
+        date: Utc.ymd(2019, month, 1).and_hms(12, 0, 0),
+        result,
+    };
+    append(
+        dir.path(),
+        &[
+            entry(4, QuestionResult::No),
+            entry(5, QuestionResult::Almost),
+        ],
+    )
+    .unwrap();
+    append(dir.path(), &[entry(5, QuestionResult::Yes)]).unwrap();
+
+    assert_eq!(
+        fs::read_to_string(dir.path().join("2019-04.jsonl"))
+            .unwrap()
+            .lines()
+            .count(),
+        1
+    );
+    assert_eq!(
+        fs::read_to_string(dir.path().join("2019-05.jsonl"))
+            .unwrap()
+            .lines()
+            .count(),
+        2
+    );
+
+    let entries = read(dir.path()).unwrap().entries;
+    let results: Vec<_> = entries.iter().map(|entry| entry.result).collect();
+    assert_eq!(
+        results,
+        vec![
+            QuestionResult::No,
+            QuestionResult::Almost,
+            QuestionResult::Yes
+        ]
+    );
+}
+
+#[test]
+fn read_ignores_partial_last_line() {
+    let dir = tempdir::TempDir::new("mathema-journal").unwrap();
+    let entries = [JournalEntry {
+        uuid: Uuid::nil(),
+        question_kind: QuestionKind::Translate {
+            from: Language::Greek,
+            to: Language::English,
+        },
+        date: Utc.ymd(2019, 5, 1).and_hms(12, 0, 0),
+        result: QuestionResult::Yes,
+    }];
+    append(dir.path(), &entries).unwrap();
+
+    let path = dir.path().join("2019-05.jsonl");
+    let mut text = fs::read_to_string(&path).unwrap();
+    text.push_str("{\"uuid\":");
+    fs::write(&path, text).unwrap();
+
+    assert_eq!(read(dir.path()).unwrap().entries.len(), 1);
+
+    // Appending after the partial line doesn't run into it.
+    append(dir.path(), &entries).unwrap();
+    assert_eq!(read(dir.path()).unwrap().entries.len(), 2);
+    assert!(fs::read_to_string(&path).unwrap().ends_with("}\n"));
+}
+
+#[test]
+fn replay_skips_duplicates() {
+    let entry = |month, result| JournalEntry {
+        uuid: Uuid::nil(),
+        question_kind: QuestionKind::Translate {
+            from: Language::Greek,
+            to: Language::English,
+        },
+        date: Utc.ymd(2019, month, 1).and_hms(12, 0, 0),
+        result,
+    };
+    let mut database = Database::empty();
+    let added = replay(
+        &mut database,
+        vec![
+            entry(5, QuestionResult::Yes),
+            entry(5, QuestionResult::Yes),
+            entry(4, QuestionResult::No),
+        ],
+    );
+    assert_eq!(added.len(), 2);
+
+    // Records stay sorted by date, whatever order the journal is in.
+    let entries = entries(&database);
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].result, QuestionResult::No);
+    assert_eq!(entries[1].result, QuestionResult::Yes);
+}
+
+#[test]
+fn deletions_outlast_merges() {
+    let dir = tempdir::TempDir::new("mathema-journal").unwrap();
+    let entry = |day| JournalEntry {
+        uuid: Uuid::nil(),
+        question_kind: QuestionKind::Translate {
+            from: Language::Greek,
+            to: Language::English,
+        },
+        date: Utc.ymd(2019, 5, day).and_hms(12, 0, 0),
+        result: QuestionResult::Yes,
+    };
+    append(dir.path(), &[entry(1)]).unwrap();
+    append_deletions(
+        dir.path(),
+        &[Deletion {
+            uuid: Uuid::nil(),
+            question_kind: None,
+            date: Utc.ymd(2019, 5, 2).and_hms(12, 0, 0),
+        }],
+    )
+    .unwrap();
+    assert!(read(dir.path()).unwrap().entries.is_empty());
+
+    // A merge brings back the deleted answer, along with a newer one.
+    append(dir.path(), &[entry(1), entry(3)]).unwrap();
+    let entries = read(dir.path()).unwrap().entries;
+    assert_eq!(entries.len(), 1);
+    assert_eq!(entries[0].date.day(), 3);
+}
+
+#[test]
+fn read_answers_in_unknown_languages() {
+    let dir = tempdir::TempDir::new("mathema-journal").unwrap();
+    fs::write(
+        dir.path().join("2019-05.jsonl"),
+        "{\"uuid\":\"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\",\
+         \"question_kind\":{\"Translate\":{\"from\":\"Klingon\",\"to\":\"English\"}},\
+         \"date\":\"2019-05-01T12:00:00Z\",\"result\":\"Almost\"}\n",
+    )
+    .unwrap();
+
+    let journal = read(dir.path()).unwrap();
+    assert!(journal.entries.is_empty());
+    assert_eq!(journal.unknown_answers.len(), 1);
+    assert_eq!(
+        journal.unknown_answers[0].question_kind.to_string(),
+        "{\"Translate\":{\"from\":\"Klingon\",\"to\":\"English\"}}"
+    );
+}
+
+#[test]
+fn read_missing_journal() {
+    assert!(read(Path::new("/nonexistent/mathema-history"))
+        .unwrap()
+        .entries
+        .is_empty());
+}
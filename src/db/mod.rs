@@ -1,20 +1,46 @@
 //! The "database" tracks which words have been asked and when. It
 //! also tracks which card files have been added to chrono. It does
-//! not track the cards themselves.
+//! not track the cards themselves. Only the card files are saved with
+//! the database; the answers are saved in the journal (see `journal`),
+//! and rebuilt from it.
 
 use crate::prelude::*;
 
+crate mod journal;
+crate mod schema;
+
 #[derive(Serialize, Deserialize)]
 pub(crate) struct Database {
+    /// Version of the format the database is saved in (see `schema`).
+    pub(crate) version: u32,
+
     /// Path to each card file, relative to our directory.
     pub(crate) card_files: Vec<PathBuf>,
 
     /// Records specific to a given user (for now, we only support one
-    /// user per directory).
+    /// user per directory). Older databases saved these, so we still
+    /// read them (and move them into the journal), but we no longer
+    /// write them.
+    #[serde(default, skip_serializing)]
     pub(crate) user: User,
+
+    /// If the database was saved in an older format, that format's
+    /// version; it is saved in the current one from now on.
+    #[serde(skip)]
+    pub(crate) upgraded_from: Option<u32>,
 }
 
-#[derive(Serialize, Deserialize)]
+/// Databases from before the journal still have answers in them. When
+/// we merge those, we write the answers back out, so that they aren't
+/// lost before the next `mathema` command moves them into the journal.
+#[derive(Serialize)]
+struct DatabaseWithRecords<'d> {
+    version: u32,
+    card_files: &'d [PathBuf],
+    user: &'d User,
+}
+
+#[derive(Default, Serialize, Deserialize)]
 pub(crate) struct User {
     pub(crate) records: HashMap<Uuid, CardRecord>,
 }
@@ -26,7 +52,7 @@ pub(crate) struct CardRecord {
 }
 
 /// A question we asked the user...
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
 pub(crate) struct QuestionRecord {
     /// ...when did we ask?
     pub(crate) date: UtcDateTime,
@@ -40,7 +66,7 @@ pub(crate) enum QuestionKind {
     Translate { from: Language, to: Language },
 }
 
-#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub(crate) enum QuestionResult {
     /// User knew it.
     Yes,
@@ -68,10 +94,12 @@ impl FromStr for QuestionResult {
 impl Database {
     crate fn empty() -> Database {
         Database {
+            version: schema::CURRENT_VERSION,
             card_files: vec![],
             user: User {
                 records: HashMap::new(),
             },
+            upgraded_from: None,
         }
     }
 
@@ -81,8 +109,32 @@ impl Database {
         Ok(())
     }
 
-    crate fn load_from(reader: impl io::Read) -> Fallible<Self> {
-        let db = ::ron::de::from_reader(reader)?;
+    /// Serializes a merged database. This is like `write_to`, except
+    /// that it keeps any answers that are still in the database.
+    crate fn to_string_keeping_records(&self) -> Fallible<String> {
+        if self.user.records.is_empty() {
+            return Ok(::ron::ser::to_string_pretty(self, Default::default())?);
+        }
+
+        let with_records = DatabaseWithRecords {
+            version: self.version,
+            card_files: &self.card_files,
+            user: &self.user,
+        };
+        Ok(::ron::ser::to_string_pretty(
+            &with_records,
+            Default::default(),
+        )?)
+    }
+
+    /// Loads a database saved in the current format or an older one
+    /// (see `schema`).
+    crate fn load_from(mut reader: impl io::Read) -> Fallible<Self> {
+        let mut text = String::new();
+        reader.read_to_string(&mut text)?;
+        let (text, upgraded_from) = schema::upgrade(text)?;
+        let mut db: Database = ::ron::de::from_str(&text)?;
+        db.upgraded_from = upgraded_from;
         Ok(db)
     }
 
@@ -100,6 +152,26 @@ impl Database {
             .entry(uuid)
             .or_insert(CardRecord::default())
     }
+
+    /// Merges `other` into `self`, as when two copies of the database
+    /// have diverged: we keep the card files of both, and every answer
+    /// from both (once).
+    crate fn merge(&mut self, other: Database) {
+        for card_file in other.card_files {
+            if !self.contains_card_file(&card_file) {
+                self.card_files.push(card_file);
+            }
+        }
+
+        for (uuid, card_record) in other.user.records {
+            let self_record = self.card_record_mut(uuid);
+            for (kind, records) in card_record.questions {
+                for record in records {
+                    self_record.insert_question_record(kind, record);
+                }
+            }
+        }
+    }
 }
 
 impl CardRecord {
@@ -107,6 +179,19 @@ impl CardRecord {
         self.questions.entry(kind).or_insert(vec![]).push(record);
     }
 
+    /// Inserts `record` in date order, unless there already is a record
+    /// of this kind from the same date. Returns true if it was inserted.
+    crate fn insert_question_record(&mut self, kind: QuestionKind, record: QuestionRecord) -> bool {
+        let questions = self.questions.entry(kind).or_insert(vec![]);
+        match questions.binary_search_by_key(&record.date, |q| q.date) {
+            Ok(_) => false,
+            Err(index) => {
+                questions.insert(index, record);
+                true
+            }
+        }
+    }
+
     crate fn last_asked(&self, kind: QuestionKind) -> Option<UtcDateTime> {
         Some(self.questions(kind).last()?.date)
     }
@@ -169,6 +254,14 @@ impl QuestionKind {
         }
     }
 
+    /// When asking a question of this kind, what language is the
+    /// prompt in?
+    crate fn prompt_language(self) -> Language {
+        match self {
+            QuestionKind::Translate { from, to: _ } => from,
+        }
+    }
+
     /// When asking a question of this kind, what language should we
     /// expect the user's response to be in?
     crate fn response_language(self) -> Language {
@@ -200,3 +293,51 @@ impl fmt::Display for PromptText {
         Ok(())
     }
 }
+
+#[test]
+fn merge_databases() {
+    let database = |card_files: &[&str], answers: &[(u32, QuestionResult)]| {
+        let mut database = Database::empty();
+        database.card_files = card_files.iter().map(PathBuf::from).collect();
+        let kind = QuestionKind::Translate {
+            from: Language::Greek,
+            to: Language::English,
+        };
+        for &(day, result) in answers {
+            database.card_record_mut(Uuid::nil()).push_question_record(
+                kind,
+                QuestionRecord {
+                    date: Utc.ymd(2019, 5, day).and_hms(12, 0, 0),
+                    result,
+                },
+            );
+        }
+        database
+    };
+
+    let mut ours = database(
+        &["a.cards", "b.cards"],
+        &[(1, QuestionResult::No), (3, QuestionResult::Yes)],
+    );
+    let theirs = database(
+        &["c.cards", "a.cards"],
+        &[(1, QuestionResult::No), (2, QuestionResult::Almost)],
+    );
+    ours.merge(theirs);
+
+    assert_eq!(
+        ours.card_files,
+        vec![
+            PathBuf::from("a.cards"),
+            PathBuf::from("b.cards"),
+            PathBuf::from("c.cards")
+        ]
+    );
+
+    let questions = &ours.card_record(Uuid::nil()).unwrap().questions;
+    let days: Vec<u32> = questions
+        .values()
+        .flat_map(|records| records.iter().map(|record| record.date.day()))
+        .collect();
+    assert_eq!(days, vec![1, 2, 3]);
+}
@@ -0,0 +1,98 @@
+//! Versions of the format the database is saved in. Each database
+//! starts with the version it was written in, like `(version: 2, ...)`;
+//! databases from before we recorded it are version 1. When we load an
+//! older database, the migrations below bring its text up to date
+//! before we parse it, so `Database` only ever has to read the current
+//! format. To change the format, bump `CURRENT_VERSION` and add a
+//! migration from the previous one.
+
+use crate::prelude::*;
+
+/// The version of the format that we write.
+crate const CURRENT_VERSION: u32 = 2;
+
+/// Rewrites the text of a database in version `from` into version
+/// `from + 1`.
+struct Migration {
+    from: u32,
+    migrate: fn(&str) -> Fallible<String>,
+}
+
+/// One migration for every version before `CURRENT_VERSION`, in order.
+const MIGRATIONS: &[Migration] = &[Migration {
+    from: 1,
+    migrate: add_version,
+}];
+
+lazy_static! {
+    static ref VERSION: Regex = Regex::new(r"^\s*\(\s*version\s*:\s*(\d+)").unwrap();
+}
+
+/// The version that the database in `text` was written in.
+crate fn version_of(text: &str) -> Fallible<u32> {
+    match VERSION.captures(text) {
+        Some(captures) => Ok(captures[1]
+            .parse()
+            .map_err(|_| MathemaErrorKind::UnrecognizedDatabaseVersion)?),
+        None => Ok(1),
+    }
+}
+
+/// Brings the database in `text` up to `CURRENT_VERSION`. Returns the
+/// upgraded text, and the version it was in if that was older.
+crate fn upgrade(text: String) -> Fallible<(String, Option<u32>)> {
+    let version = version_of(&text)?;
+    if version > CURRENT_VERSION {
+        throw!(MathemaErrorKind::DatabaseTooNew {
+            version,
+            supported: CURRENT_VERSION,
+        });
+    }
+    if version == CURRENT_VERSION {
+        return Ok((text, None));
+    }
+
+    let mut text = text;
+    for migration in MIGRATIONS.iter().filter(|m| m.from >= version) {
+        text = (migration.migrate)(&text)?;
+    }
+    Ok((text, Some(version)))
+}
+
+/// Version 2 adds the version itself, as the first field.
+fn add_version(text: &str) -> Fallible<String> {
+    match text.find('(') {
+        Some(index) => Ok(format!(
+            "{}(\n    version: 2,{}",
+            &text[..index],
+            &text[index + 1..]
+        )),
+        None => throw!(MathemaErrorKind::UnrecognizedDatabaseVersion),
+    }
+}
+
+#[test]
+fn migrations_cover_every_version() {
+    let froms: Vec<u32> = MIGRATIONS.iter().map(|m| m.from).collect();
+    let expected: Vec<u32> = (1..CURRENT_VERSION).collect();
+    assert_eq!(froms, expected);
+}
+
+#[test]
+fn version_1_is_upgraded() {
+    let (text, upgraded_from) = upgrade("(card_files: [\"a.cards\"])".to_string()).unwrap();
+    assert_eq!(upgraded_from, Some(1));
+    assert_eq!(version_of(&text).unwrap(), CURRENT_VERSION);
+    let database = Database::load_from(text.as_bytes()).unwrap();
+    assert_eq!(database.card_files, vec![PathBuf::from("a.cards")]);
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let error = upgrade("(version: 99, card_files: [])".to_string()).unwrap_err();
+    assert!(
+        error.to_string().contains("version 99"),
+        "error is `{}`",
+        error
+    );
+}
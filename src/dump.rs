@@ -7,12 +7,7 @@ crate fn dump(options: &MathemaOptions, filter: &Option<String>, expired: bool)
         return Ok(());
     }
 
-    let mut question_kinds: Vec<QuestionKind> = SUITABLE_QUESTIONS
-        .iter()
-        .flat_map(|(_lang, kinds)| kinds.iter().cloned())
-        .collect();
-    question_kinds.sort();
-    question_kinds.dedup();
+    let question_kinds = quiz::all_question_kinds();
 
     let stdout = &mut std::io::stdout();
 
@@ -32,6 +32,24 @@ pub(crate) enum MathemaErrorKind {
     )]
     UnrecognizedPresentationMode { text: String },
 
+    #[fail(
+        display = "`{}` is not a recognized export format (try anki-tsv, json, yaml, html or markdown)",
+        text
+    )]
+    UnrecognizedExportFormat { text: String },
+
+    #[fail(
+        display = "the `{}` export format needs an output directory (try `-o`)",
+        format
+    )]
+    OutputDirectoryRequired { format: &'static str },
+
+    #[fail(
+        display = "cannot import from the `{}` format (try json or yaml)",
+        format
+    )]
+    CannotImportFormat { format: &'static str },
+
     #[fail(
         display = "the option `{}` is not compatible with the command `{}`",
         option, command
@@ -80,12 +98,73 @@ pub(crate) enum MathemaErrorKind {
     #[fail(display = "Failed to create directory `{}`", directory_path)]
     CreatingDir { directory_path: String },
 
+    #[fail(
+        display = "`{}` already exists; import into a new directory",
+        directory_path
+    )]
+    DirectoryExists { directory_path: String },
+
     #[fail(display = "Cannot load Mathema database from `{}`", database_path)]
     CannotLoadDatabase { database_path: String },
 
-    #[fail(display = "No git repository found in `{}`", directory_path)]
+    #[fail(
+        display = "The database was written by a newer mathema (format version {}, but this one only reads up to version {}); upgrade mathema",
+        version, supported
+    )]
+    DatabaseTooNew { version: u32, supported: u32 },
+
+    #[fail(display = "The database does not say which version of the format it is in")]
+    UnrecognizedDatabaseVersion,
+
+    #[fail(display = "Cannot load deck configuration from `{}`", config_path)]
+    CannotLoadConfig { config_path: String },
+
+    #[fail(display = "Cannot load user configuration from `{}`", config_path)]
+    CannotLoadUserConfig { config_path: String },
+
+    #[fail(display = "Language code `{}` is already used by {}", code, language)]
+    LanguageCodeInUse { code: String, language: String },
+
+    #[fail(
+        display = "No git repository found in `{}` (to do without git, add `storage: Directory` to `mathema-config.ron`)",
+        directory_path
+    )]
     NoGitRepositoryFound { directory_path: String },
 
+    #[fail(
+        display = "No git remote named `{}` (try `git remote add {} <url>`)",
+        remote, remote
+    )]
+    NoRemote { remote: &'static str },
+
+    #[fail(display = "Only decks kept in git can be synced (this one has `storage: Directory`)")]
+    SyncNeedsGit,
+
+    #[fail(display = "Cannot sync while not on a branch")]
+    NotOnBranch,
+
+    #[fail(
+        display = "{} changed both here and in `{}`; merge with `git pull {} {}`, then sync again",
+        files, remote, remote, branch
+    )]
+    SyncConflicts {
+        files: String,
+        remote: &'static str,
+        branch: String,
+    },
+
+    #[fail(display = "`{}` refused the push: {}", remote, message)]
+    PushRejected {
+        remote: &'static str,
+        message: String,
+    },
+
+    #[fail(display = "Signing commits with SSH needs a key (try `git config user.signingkey`)")]
+    NoSigningKey,
+
+    #[fail(display = "Cannot sign the commit with `{}`: {}", program, message)]
+    SigningFailed { program: String, message: String },
+
     #[fail(display = "Unexpected error encountered")]
     Unexpected,
 }
@@ -141,6 +220,8 @@ link_unexpected! {
     ::git2::Error,
     ::ron::de::Error,
     ::ron::ser::Error,
+    ::serde_json::Error,
+    ::serde_yaml::Error,
 }
 
 impl<E> From<atomicwrites::Error<E>> for MathemaError
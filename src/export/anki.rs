@@ -0,0 +1,78 @@
+//! Writes cards as a tab-separated file that Anki's "Import File"
+//! dialog understands. The header lines tell Anki which column holds
+//! the note GUID and which holds the tags; we use the card's UUID as
+//! the GUID, so importing a newer export updates the existing notes
+//! rather than duplicating them.
+
+use super::{escape_html, languages_used};
+use crate::prelude::*;
+
+crate fn write_anki_tsv(cards: &[&Card], output: &mut dyn io::Write) -> Fallible<()> {
+    let languages = languages_used(cards);
+    let mut columns = vec!["guid"];
+    columns.extend(languages.iter().map(|l| l.abbreviation()));
+    columns.extend(&["pos", "aorist", "tags"]);
+
+    writeln!(output, "#separator:tab")?;
+    writeln!(output, "#html:true")?;
+    writeln!(output, "#columns:{}", columns.join("\t"))?;
+    writeln!(output, "#guid column:1")?;
+    writeln!(output, "#tags column:{}", columns.len())?;
+
+    for card in cards {
+        let uuid = card.uuid.expect("loaded cards always have a UUID");
+        let mut fields = vec![uuid.to_string()];
+        for &language in &languages {
+            fields.push(field(card.meanings(language)));
+        }
+        fields.push(field(card.lines_with_kind(LineKind::PartOfSpeech)));
+        fields.push(field(card.lines_with_kind(LineKind::Aoristos)));
+        fields.push(card.tags().join(" "));
+        writeln!(output, "{}", fields.join("\t"))?;
+    }
+
+    Ok(())
+}
+
+/// Joins the lines of a card into one field, one line per row. Tabs
+/// would start a new column, so they become spaces.
+fn field<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    lines
+        .map(|line| escape_html(&line.replace('\t', " ")))
+        .join("<br>")
+}
+
+#[test]
+fn anki_tsv_fields() {
+    let card = Card {
+        uuid: Some(Uuid::parse_str("d2b0a1d6-3c39-4f36-8f5d-0a4b2d6e6c43").unwrap()),
+        start_line: 1,
+        lines: vec![
+            CardLine {
+                kind: LineKind::Meaning(Language::English),
+                text: "lesson".to_string(),
+            },
+            CardLine {
+                kind: LineKind::Meaning(Language::Greek),
+                text: "το μάθημα".to_string(),
+            },
+            CardLine {
+                kind: LineKind::Meaning(Language::English),
+                text: "class <school>".to_string(),
+            },
+            CardLine {
+                kind: LineKind::Tags,
+                text: "school  nouns".to_string(),
+            },
+        ],
+    };
+
+    let mut output = vec![];
+    write_anki_tsv(&[&card], &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let last_line = output.lines().last().unwrap();
+    assert_eq!(
+        last_line,
+        "d2b0a1d6-3c39-4f36-8f5d-0a4b2d6e6c43\tlesson<br>class &lt;school&gt;\tτο μάθημα\t\t\tschool nouns"
+    );
+}
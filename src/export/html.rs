@@ -0,0 +1,297 @@
+//! Writes a deck as a small static site: an index plus one page per
+//! card file, laid out in directories like the card files are. The
+//! stylesheet is inlined into every page, so the
+//! directory can be copied anywhere or opened straight from disk.
+//!
+//! With `--flashcards`, each page also carries a printable version of
+//! its cards: sheets of fronts (the English side) alternating with
+//! sheets of backs, laid out so that printing double-sided and cutting
+//! along the dashed lines yields paper flashcards.
+
+use super::{escape_html, write_file};
+use crate::prelude::*;
+
+const FLASHCARD_COLUMNS: usize = 2;
+const FLASHCARD_ROWS: usize = 5;
+
+const STYLE: &str = "
+body { font-family: sans-serif; max-width: 50em; margin: 2em auto; }
+.card { border: 1px solid #ccc; border-radius: 4px; margin: 1em 0; padding: 0.5em 1em; }
+.card th { text-align: left; padding-right: 1em; color: #666; font-weight: normal; }
+.card .comment td { font-style: italic; color: #666; }
+.flashcards { display: none; }
+
+@media print {
+  body { max-width: none; margin: 0; }
+  nav, h1, .cards { display: none; }
+  .flashcards { display: block; }
+  .sheet {
+    display: grid;
+    height: 100vh;
+    page-break-after: always;
+  }
+  .flashcard {
+    border: 1px dashed #999;
+    display: flex;
+    flex-direction: column;
+    align-items: center;
+    justify-content: center;
+    text-align: center;
+    font-size: 16pt;
+  }
+  .flashcard .grammar { font-size: 11pt; color: #444; }
+}
+";
+
+crate fn write_html(repo: &MathemaRepository, directory: &Path, flashcards: bool) -> Fallible<()> {
+    fs::create_dir_all(directory).with_context(|_| MathemaErrorKind::CreatingDir {
+        directory_path: directory.display().to_string(),
+    })?;
+
+    let card_files = &repo.database().card_files;
+    write_page(&directory.join("index.html"), |out| {
+        write_index(out, repo, card_files)
+    })?;
+
+    for card_file in card_files {
+        let cards = repo.cards_in_file(card_file);
+        write_page(&directory.join(page_path(card_file)), |out| {
+            write_card_file_page(out, card_file, &cards, flashcards)
+        })?;
+    }
+
+    println!(
+        "Wrote {} pages to `{}`.",
+        card_files.len() + 1,
+        directory.display()
+    );
+
+    Ok(())
+}
+
+fn write_page(path: &Path, op: impl FnOnce(&mut File) -> Fallible<()>) -> Fallible<()> {
+    let result: Fallible<()> = try {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        write_file(path, op)?;
+    };
+    result.with_context(|_| MathemaErrorKind::AccessingFile {
+        file: path.display().to_string(),
+    })?;
+    Ok(())
+}
+
+/// Where a card file's page goes, relative to the index:
+/// `verbs/aorist.cards` becomes `verbs/aorist.cards.html`. Flattening
+/// the directories away would give `a/b.cards` and `a_b.cards` the
+/// same page.
+fn page_path(card_file: &Path) -> PathBuf {
+    let mut path = card_file.as_os_str().to_owned();
+    path.push(".html");
+    PathBuf::from(path)
+}
+
+/// The link from the index to a card file's page. Unlike paths, URLs
+/// always use `/`.
+fn page_href(card_file: &Path) -> String {
+    let components: Vec<_> = card_file
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    format!("{}.html", components.join("/"))
+}
+
+/// The link from a card file's page back to the index.
+fn index_href(card_file: &Path) -> String {
+    let depth = card_file.components().count().saturating_sub(1);
+    format!("{}index.html", "../".repeat(depth))
+}
+
+/// The grid of a sheet of flashcards, which must match the number of
+/// cards that `write_flashcards` puts on one.
+fn sheet_style() -> String {
+    format!(
+        "@media print {{ .sheet {{ grid-template-columns: repeat({}, 1fr); \
+         grid-template-rows: repeat({}, 1fr); }} }}",
+        FLASHCARD_COLUMNS, FLASHCARD_ROWS
+    )
+}
+
+fn write_header(out: &mut dyn io::Write, title: &str) -> Fallible<()> {
+    writeln!(out, "<!DOCTYPE html>")?;
+    writeln!(out, "<html>")?;
+    writeln!(out, "<head>")?;
+    writeln!(out, "<meta charset=\"utf-8\">")?;
+    writeln!(out, "<title>{}</title>", escape_html(title))?;
+    writeln!(out, "<style>{}{}</style>", STYLE, sheet_style())?;
+    writeln!(out, "</head>")?;
+    writeln!(out, "<body>")?;
+    Ok(())
+}
+
+fn write_footer(out: &mut dyn io::Write) -> Fallible<()> {
+    writeln!(out, "</body>")?;
+    writeln!(out, "</html>")?;
+    Ok(())
+}
+
+fn write_index(
+    out: &mut dyn io::Write,
+    repo: &MathemaRepository,
+    card_files: &[PathBuf],
+) -> Fallible<()> {
+    write_header(out, "μάθημα")?;
+    writeln!(out, "<h1>μάθημα</h1>")?;
+    writeln!(out, "<ul>")?;
+    for card_file in card_files {
+        writeln!(
+            out,
+            "<li><a href=\"{}\">{}</a> ({} cards)</li>",
+            escape_html(&page_href(card_file)),
+            escape_html(&card_file.display().to_string()),
+            repo.cards_in_file(card_file).len(),
+        )?;
+    }
+    writeln!(out, "</ul>")?;
+    write_footer(out)
+}
+
+fn write_card_file_page(
+    out: &mut dyn io::Write,
+    card_file: &Path,
+    cards: &[&Card],
+    flashcards: bool,
+) -> Fallible<()> {
+    let title = card_file.display().to_string();
+    write_header(out, &title)?;
+    writeln!(
+        out,
+        "<nav><a href=\"{}\">All card files</a></nav>",
+        escape_html(&index_href(card_file))
+    )?;
+    writeln!(out, "<h1>{}</h1>", escape_html(&title))?;
+
+    writeln!(out, "<div class=\"cards\">")?;
+    for card in cards {
+        writeln!(out, "<section class=\"card\">")?;
+        writeln!(out, "<table>")?;
+        for line in &card.lines {
+            match line.kind {
+                LineKind::Comment => writeln!(
+                    out,
+                    "<tr class=\"comment\"><td colspan=\"2\">{}</td></tr>",
+                    escape_html(&line.text)
+                )?,
+                _ => writeln!(
+                    out,
+                    "<tr><th>{}</th><td>{}</td></tr>",
+                    escape_html(&line.kind.to_string()),
+                    escape_html(&line.text)
+                )?,
+            }
+        }
+        writeln!(out, "</table>")?;
+        writeln!(out, "</section>")?;
+    }
+    writeln!(out, "</div>")?;
+
+    if flashcards {
+        write_flashcards(out, cards)?;
+    }
+
+    write_footer(out)
+}
+
+/// Writes the printable flashcards: for each sheet of fronts, a sheet
+/// of backs follows. On the back sheet every row is mirrored, so that
+/// after flipping the paper along its long edge each back lands
+/// behind its front.
+fn write_flashcards(out: &mut dyn io::Write, cards: &[&Card]) -> Fallible<()> {
+    writeln!(out, "<div class=\"flashcards\">")?;
+    for sheet in cards.chunks(FLASHCARD_COLUMNS * FLASHCARD_ROWS) {
+        let mut slots: Vec<Option<&Card>> = sheet.iter().map(|&card| Some(card)).collect();
+        slots.resize(FLASHCARD_COLUMNS * FLASHCARD_ROWS, None);
+
+        writeln!(out, "<div class=\"sheet\">")?;
+        for slot in &slots {
+            writeln!(out, "<div class=\"flashcard\">")?;
+            if let Some(card) = slot {
+                for meaning in card.meanings(Language::English) {
+                    writeln!(out, "<div>{}</div>", escape_html(meaning))?;
+                }
+            }
+            writeln!(out, "</div>")?;
+        }
+        writeln!(out, "</div>")?;
+
+        writeln!(out, "<div class=\"sheet\">")?;
+        for row in slots.chunks(FLASHCARD_COLUMNS) {
+            for slot in row.iter().rev() {
+                writeln!(out, "<div class=\"flashcard\">")?;
+                if let Some(card) = slot {
+                    write_flashcard_back(out, card)?;
+                }
+                writeln!(out, "</div>")?;
+            }
+        }
+        writeln!(out, "</div>")?;
+    }
+    writeln!(out, "</div>")?;
+    Ok(())
+}
+
+fn write_flashcard_back(out: &mut dyn io::Write, card: &Card) -> Fallible<()> {
+    for line in &card.lines {
+        match line.kind {
+            LineKind::Meaning(Language::English) | LineKind::Comment | LineKind::Tags => {}
+            LineKind::Meaning(_) => writeln!(out, "<div>{}</div>", escape_html(&line.text))?,
+            LineKind::PartOfSpeech | LineKind::Aoristos => writeln!(
+                out,
+                "<div class=\"grammar\">{} {}</div>",
+                escape_html(&line.kind.to_string()),
+                escape_html(&line.text)
+            )?,
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn page_names() {
+    assert_eq!(
+        page_path(Path::new("lesson.cards")),
+        Path::new("lesson.cards.html")
+    );
+    assert_eq!(
+        page_path(Path::new("verbs/aorist.cards")),
+        Path::new("verbs/aorist.cards.html")
+    );
+    assert_ne!(
+        page_path(Path::new("a/b.cards")),
+        page_path(Path::new("a_b.cards"))
+    );
+
+    assert_eq!(
+        page_href(Path::new("verbs/aorist.cards")),
+        "verbs/aorist.cards.html"
+    );
+    assert_eq!(index_href(Path::new("lesson.cards")), "index.html");
+    assert_eq!(
+        index_href(Path::new("verbs/past/aorist.cards")),
+        "../../index.html"
+    );
+}
+
+#[test]
+fn sheet_matches_flashcard_layout() {
+    let style = sheet_style();
+    assert!(style.contains(&format!(
+        "grid-template-columns: repeat({}, 1fr)",
+        FLASHCARD_COLUMNS
+    )));
+    assert!(style.contains(&format!(
+        "grid-template-rows: repeat({}, 1fr)",
+        FLASHCARD_ROWS
+    )));
+}
@@ -0,0 +1,140 @@
+//! Writes the deck as Markdown vocabulary tables, one table per card
+//! file (or, with `--by-tag`, per tag). With `--intervals`, each table
+//! gets an extra column per question kind showing how long we currently
+//! wait between asking that question, which is a rough measure of how
+//! well the card is known.
+
+use super::{languages_used, ExportOptions};
+use crate::prelude::*;
+
+crate fn write_markdown(
+    repo: &MathemaRepository,
+    export_options: &ExportOptions,
+    output: &mut dyn io::Write,
+) -> Fallible<()> {
+    let languages = languages_used(&repo.cards().values().collect::<Vec<_>>());
+    let question_kinds: Vec<QuestionKind> = if export_options.intervals {
+        quiz::all_question_kinds()
+            .into_iter()
+            .filter(|&kind| match kind {
+                QuestionKind::Translate { from, to } => {
+                    languages.contains(&from) && languages.contains(&to)
+                }
+            })
+            .collect()
+    } else {
+        vec![]
+    };
+
+    let mut sep = "";
+    for (heading, cards) in groups(repo, export_options.by_tag) {
+        write!(output, "{}", sep)?;
+        sep = "\n";
+
+        writeln!(output, "## {}", heading)?;
+        writeln!(output)?;
+        write_table(output, repo, &cards, &languages, &question_kinds)?;
+    }
+
+    Ok(())
+}
+
+/// Returns the headings and cards of each table, in order.
+fn groups(repo: &MathemaRepository, by_tag: bool) -> Vec<(String, Vec<&Card>)> {
+    let card_files = &repo.database().card_files;
+    if !by_tag {
+        return card_files
+            .iter()
+            .map(|card_file| {
+                (
+                    card_file.display().to_string(),
+                    repo.cards_in_file(card_file),
+                )
+            })
+            .collect();
+    }
+
+    // A card with several tags shows up under each of them.
+    let mut by_tag: BTreeMap<String, Vec<&Card>> = BTreeMap::new();
+    let mut untagged = vec![];
+    for card_file in card_files {
+        for card in repo.cards_in_file(card_file) {
+            let mut tagged = false;
+            for tag in card.tags() {
+                by_tag.entry(tag.to_string()).or_insert(vec![]).push(card);
+                tagged = true;
+            }
+            if !tagged {
+                untagged.push(card);
+            }
+        }
+    }
+
+    let mut groups: Vec<_> = by_tag.into_iter().collect();
+    if !untagged.is_empty() {
+        groups.push(("(untagged)".to_string(), untagged));
+    }
+    groups
+}
+
+fn write_table(
+    output: &mut dyn io::Write,
+    repo: &MathemaRepository,
+    cards: &[&Card],
+    languages: &[Language],
+    question_kinds: &[QuestionKind],
+) -> Fallible<()> {
+    let mut header: Vec<String> = languages
+        .iter()
+        .map(|l| l.full_name().to_string())
+        .collect();
+    header.push("pos".to_string());
+    for &kind in question_kinds {
+        match kind {
+            QuestionKind::Translate { from, to } => {
+                header.push(format!("{} → {}", from.abbreviation(), to.abbreviation()))
+            }
+        }
+    }
+    write_row(output, &header)?;
+    write_row(output, &vec!["---".to_string(); header.len()])?;
+
+    for card in cards {
+        let mut row: Vec<String> = languages
+            .iter()
+            .map(|&l| cell(card.meanings(l)))
+            .collect();
+        row.push(cell(card.lines_with_kind(LineKind::PartOfSpeech)));
+        for &kind in question_kinds {
+            row.push(interval(repo, card, kind));
+        }
+        write_row(output, &row)?;
+    }
+
+    Ok(())
+}
+
+fn write_row(output: &mut dyn io::Write, cells: &[String]) -> Fallible<()> {
+    writeln!(output, "| {} |", cells.join(" | "))?;
+    Ok(())
+}
+
+/// Joins several lines into one table cell. Pipes would end the
+/// cell early, so they are escaped.
+fn cell<'a>(lines: impl Iterator<Item = &'a str>) -> String {
+    lines.map(|line| line.replace('|', "\\|")).join(" / ")
+}
+
+fn interval(repo: &MathemaRepository, card: &Card, kind: QuestionKind) -> String {
+    let record = match card.uuid.and_then(|uuid| repo.database().card_record(uuid)) {
+        Some(record) => record,
+        None => return "new".to_string(),
+    };
+
+    match selection::expiration_duration(kind, record) {
+        Some(duration) if duration.num_days() >= 1 => format!("{} days", duration.num_days()),
+        Some(duration) => format!("{} hours", duration.num_hours()),
+        None if record.questions(kind).is_empty() => "new".to_string(),
+        None => "learning".to_string(),
+    }
+}
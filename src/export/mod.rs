@@ -0,0 +1,199 @@
+//! Export subcommand.
+
+use crate::prelude::*;
+
+mod anki;
+mod html;
+mod markdown;
+crate mod snapshot;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+crate enum ExportFormat {
+    AnkiTsv,
+    Json,
+    Yaml,
+    Html,
+    Markdown,
+}
+
+/// Options that only make sense for some export formats.
+crate struct ExportOptions {
+    /// Add printable flashcards to the html pages.
+    crate flashcards: bool,
+
+    /// Group the markdown tables by tag rather than by card file.
+    crate by_tag: bool,
+
+    /// Add a column with each card's current interval to the markdown
+    /// tables.
+    crate intervals: bool,
+}
+
+impl ExportFormat {
+    crate fn name(self) -> &'static str {
+        match self {
+            ExportFormat::AnkiTsv => "anki-tsv",
+            ExportFormat::Json => "json",
+            ExportFormat::Yaml => "yaml",
+            ExportFormat::Html => "html",
+            ExportFormat::Markdown => "markdown",
+        }
+    }
+
+    /// Guesses the format of a file from its extension, for commands
+    /// where `--format` is optional.
+    crate fn from_extension(path: &Path) -> Option<ExportFormat> {
+        match path.extension()?.to_str()? {
+            "json" => Some(ExportFormat::Json),
+            "yaml" | "yml" => Some(ExportFormat::Yaml),
+            "tsv" => Some(ExportFormat::AnkiTsv),
+            "md" => Some(ExportFormat::Markdown),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = MathemaError;
+
+    fn from_str(s: &str) -> Fallible<ExportFormat> {
+        match s {
+            "anki-tsv" => Ok(ExportFormat::AnkiTsv),
+            "json" => Ok(ExportFormat::Json),
+            "yaml" => Ok(ExportFormat::Yaml),
+            "html" => Ok(ExportFormat::Html),
+            "markdown" => Ok(ExportFormat::Markdown),
+            _ => throw!(MathemaErrorKind::UnrecognizedExportFormat {
+                text: s.to_string(),
+            }),
+        }
+    }
+}
+
+crate fn export(
+    options: &MathemaOptions,
+    format: ExportFormat,
+    output: &Option<String>,
+    export_options: &ExportOptions,
+) -> Fallible<()> {
+    let ExportOptions {
+        flashcards,
+        by_tag,
+        intervals,
+    } = *export_options;
+
+    if flashcards && format != ExportFormat::Html {
+        throw!(MathemaErrorKind::IncompatibleOption {
+            option: "--flashcards",
+            command: "export",
+        });
+    }
+
+    for &(set, option) in &[(by_tag, "--by-tag"), (intervals, "--intervals")] {
+        if set && format != ExportFormat::Markdown {
+            throw!(MathemaErrorKind::IncompatibleOption {
+                option,
+                command: "export",
+            });
+        }
+    }
+
+    let repo = &mut MathemaRepository::open(options)?;
+    let status = repo.load_cards()?;
+    if status.warn_if_needed(options.force) {
+        return Ok(());
+    }
+
+    // A site has many pages, so it needs a directory to go into.
+    if format == ExportFormat::Html {
+        match output {
+            Some(output) => html::write_html(repo, Path::new(output), flashcards)?,
+            None => throw!(MathemaErrorKind::OutputDirectoryRequired {
+                format: format.name(),
+            }),
+        }
+        return Ok(());
+    }
+
+    match output {
+        Some(output) => {
+            let path = Path::new(output);
+            let result = write_file(path, |f| write_export(format, repo, export_options, f));
+            result.with_context(|_| MathemaErrorKind::AccessingFile {
+                file: path.display().to_string(),
+            })?;
+        }
+
+        None => {
+            write_export(format, repo, export_options, &mut io::stdout())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_export(
+    format: ExportFormat,
+    repo: &MathemaRepository,
+    export_options: &ExportOptions,
+    output: &mut dyn io::Write,
+) -> Fallible<()> {
+    match format {
+        ExportFormat::AnkiTsv => anki::write_anki_tsv(&sorted_cards(repo), output),
+        ExportFormat::Json => {
+            let snapshot = snapshot::DeckSnapshot::from_repo(repo)?;
+            serde_json::to_writer_pretty(&mut *output, &snapshot)?;
+            writeln!(output)?;
+            Ok(())
+        }
+        ExportFormat::Yaml => {
+            let snapshot = snapshot::DeckSnapshot::from_repo(repo)?;
+            serde_yaml::to_writer(&mut *output, &snapshot)?;
+            writeln!(output)?;
+            Ok(())
+        }
+        ExportFormat::Markdown => markdown::write_markdown(repo, export_options, output),
+        ExportFormat::Html => unreachable!("html is written to a directory"),
+    }
+}
+
+/// Returns the loaded cards, sorted so that exporting the same deck
+/// twice yields the same output.
+fn sorted_cards(repo: &MathemaRepository) -> Vec<&Card> {
+    let mut cards: Vec<&Card> = repo.cards().values().collect();
+    cards.sort_by_key(|card| card.uuid);
+    cards
+}
+
+/// The languages that some card has a meaning in, so that exports
+/// don't have columns for languages the deck doesn't use.
+fn languages_used(cards: &[&Card]) -> Vec<Language> {
+    Language::all()
+        .into_iter()
+        .filter(|&language| {
+            cards
+                .iter()
+                .any(|card| card.meanings(language).next().is_some())
+        })
+        .collect()
+}
+
+fn write_file(path: &Path, op: impl FnOnce(&mut File) -> Fallible<()>) -> Fallible<()> {
+    AtomicFile::new(path, OverwriteBehavior::AllowOverwrite).write(op)?;
+    Ok(())
+}
+
+/// Escapes the characters that are significant in HTML text.
+crate fn escape_html(text: &str) -> String {
+    let mut result = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => result.push_str("&amp;"),
+            '<' => result.push_str("&lt;"),
+            '>' => result.push_str("&gt;"),
+            '"' => result.push_str("&quot;"),
+            _ => result.push(c),
+        }
+    }
+    result
+}
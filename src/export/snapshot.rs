@@ -0,0 +1,105 @@
+//! A lossless, self-contained snapshot of a deck: its configuration, the
+//! contents of every registered card file and the complete question
+//! history. Unlike the RON database, it serializes cleanly to JSON and
+//! YAML, so it is what `export --format json` writes and what `import`
+//! reads back.
+
+use crate::prelude::*;
+
+#[derive(Serialize, Deserialize)]
+crate struct DeckSnapshot {
+    /// The deck's `mathema-config.ron`. It comes first, as it defines
+    /// the languages that the cards and records may be in.
+    #[serde(default)]
+    crate config: DeckConfig,
+
+    crate card_files: Vec<CardFileSnapshot>,
+    crate records: Vec<CardRecordSnapshot>,
+}
+
+/// Just the configuration of a snapshot. We read it on its own first,
+/// so that we can define its languages before reading the rest.
+#[derive(Deserialize)]
+crate struct SnapshotConfig {
+    #[serde(default)]
+    crate config: DeckConfig,
+}
+
+#[derive(Serialize, Deserialize)]
+crate struct CardFileSnapshot {
+    /// Path to the card file, relative to the deck directory.
+    crate path: PathBuf,
+
+    /// Every card in the file, including those that are only comments.
+    crate cards: Vec<Card>,
+}
+
+/// Mirrors `CardRecord`, but keeps the questions in a list: JSON only
+/// permits strings as map keys, and `QuestionKind` is not one.
+#[derive(Serialize, Deserialize)]
+crate struct CardRecordSnapshot {
+    crate uuid: Uuid,
+    crate questions: Vec<QuestionsSnapshot>,
+}
+
+#[derive(Serialize, Deserialize)]
+crate struct QuestionsSnapshot {
+    crate kind: QuestionKind,
+
+    /// Sorted by date, as in `CardRecord`.
+    crate records: Vec<QuestionRecord>,
+}
+
+impl DeckSnapshot {
+    crate fn from_repo(repo: &MathemaRepository) -> Fallible<DeckSnapshot> {
+        let db = repo.database();
+
+        let mut card_files = vec![];
+        for path in &db.card_files {
+            card_files.push(CardFileSnapshot {
+                path: path.clone(),
+                cards: repo.parse_card_file_from_repo(path)?,
+            });
+        }
+
+        let mut records: Vec<CardRecordSnapshot> = db
+            .user
+            .records
+            .iter()
+            .map(|(&uuid, record)| {
+                let mut questions: Vec<QuestionsSnapshot> = record
+                    .questions
+                    .iter()
+                    .map(|(&kind, records)| QuestionsSnapshot {
+                        kind,
+                        records: records.clone(),
+                    })
+                    .collect();
+                questions.sort_by_key(|q| q.kind);
+                CardRecordSnapshot { uuid, questions }
+            })
+            .collect();
+        records.sort_by_key(|r| r.uuid);
+
+        Ok(DeckSnapshot {
+            config: repo.config().clone(),
+            card_files,
+            records,
+        })
+    }
+
+    /// Rebuilds the database that this snapshot was taken from.
+    crate fn database(&self) -> Database {
+        let mut database = Database::empty();
+        database.card_files = self.card_files.iter().map(|f| f.path.clone()).collect();
+        for record in &self.records {
+            let card_record = database.card_record_mut(record.uuid);
+            for questions in &record.questions {
+                card_record
+                    .questions
+                    .insert(questions.kind, questions.records.clone());
+            }
+        }
+        database
+    }
+}
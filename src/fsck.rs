@@ -0,0 +1,288 @@
+//! Fsck subcommand: cross-checks the database against the card files,
+//! and (with `--repair`) fixes what it finds.
+
+use crate::db::journal::Deletion;
+use crate::prelude::*;
+
+/// Everything that is wrong with a deck.
+#[derive(Default)]
+struct Problems {
+    /// Card files in the database that are no longer on disk.
+    missing_card_files: Vec<PathBuf>,
+
+    /// Cards that have answers but are no longer in any card file,
+    /// with how many answers they have.
+    orphaned_records: BTreeMap<Uuid, usize>,
+
+    /// UUIDs shared by more than one card, with the card file and line
+    /// of each card, in the order that `load_cards` finds them.
+    duplicate_uuids: BTreeMap<Uuid, Vec<(PathBuf, u64)>>,
+
+    /// Answers to questions that we no longer ask (see
+    /// `quiz::all_question_kinds`), with how many there are.
+    unknown_question_kinds: BTreeMap<(Uuid, QuestionKind), usize>,
+
+    /// Answers to questions in languages that the deck doesn't define,
+    /// by the question kind as the journal has it, with how many there
+    /// are.
+    unknown_languages: BTreeMap<(Uuid, String), usize>,
+}
+
+crate fn fsck(options: &MathemaOptions, repair: bool) -> Fallible<()> {
+    if options.dry_run && repair {
+        throw!(MathemaErrorKind::IncompatibleOption {
+            option: "--dry-run",
+            command: "fsck --repair",
+        });
+    }
+
+    let repo = &mut MathemaRepository::open(options)?;
+    let problems = Problems::find(repo)?;
+    if problems.is_empty() {
+        println!("No problems found.");
+        return Ok(());
+    }
+
+    problems.report();
+    println!("");
+    if repair {
+        let summary = problems.repair(repo)?;
+        println!("Repaired the deck: {}.", summary);
+    } else {
+        println!("Run `mathema fsck --repair` to fix these.");
+    }
+
+    Ok(())
+}
+
+impl Problems {
+    fn find(repo: &MathemaRepository) -> Fallible<Problems> {
+        let mut problems = Problems::default();
+
+        // Where each card is. We don't use `load_cards`, which gives up
+        // on missing card files and keeps only the first of the cards
+        // that share a UUID.
+        let mut card_locations: HashMap<Uuid, Vec<(PathBuf, u64)>> = HashMap::new();
+        for card_file in &repo.database().card_files {
+            if !repo.absolute_path(card_file).exists() {
+                problems.missing_card_files.push(card_file.clone());
+                continue;
+            }
+
+            for card in repo.parse_card_file_from_repo(card_file)? {
+                if let Some(uuid) = card.uuid {
+                    card_locations
+                        .entry(uuid)
+                        .or_default()
+                        .push((card_file.clone(), card.start_line));
+                }
+            }
+        }
+        for (&uuid, locations) in &card_locations {
+            if locations.len() > 1 {
+                problems.duplicate_uuids.insert(uuid, locations.clone());
+            }
+        }
+
+        let question_kinds = quiz::all_question_kinds();
+        for (&uuid, card_record) in &repo.database().user.records {
+            if !card_locations.contains_key(&uuid) {
+                let answers: usize = card_record.questions.values().map(|r| r.len()).sum();
+                if answers > 0 {
+                    problems.orphaned_records.insert(uuid, answers);
+                }
+                continue;
+            }
+
+            for (&kind, records) in &card_record.questions {
+                if !question_kinds.contains(&kind) {
+                    problems
+                        .unknown_question_kinds
+                        .insert((uuid, kind), records.len());
+                }
+            }
+        }
+
+        // The same answer may be in the journal twice, after a merge.
+        let unknown_answers: BTreeSet<(Uuid, String, UtcDateTime)> = repo
+            .unknown_answers()
+            .iter()
+            .map(|answer| (answer.uuid, answer.question_kind.to_string(), answer.date))
+            .collect();
+        for (uuid, kind, _) in unknown_answers {
+            *problems.unknown_languages.entry((uuid, kind)).or_default() += 1;
+        }
+
+        Ok(problems)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.missing_card_files.is_empty()
+            && self.orphaned_records.is_empty()
+            && self.duplicate_uuids.is_empty()
+            && self.unknown_question_kinds.is_empty()
+            && self.unknown_languages.is_empty()
+    }
+
+    fn report(&self) {
+        let mut needs_separator = false;
+        let mut header = |text: &str| {
+            if mem::replace(&mut needs_separator, true) {
+                println!("");
+            }
+            println!("{}", text);
+        };
+
+        if !self.missing_card_files.is_empty() {
+            header("Card files that no longer exist:");
+            for card_file in &self.missing_card_files {
+                println!("  {}", card_file.display());
+            }
+        }
+
+        if !self.orphaned_records.is_empty() {
+            header("Answers to cards that no longer exist:");
+            for (uuid, &answers) in &self.orphaned_records {
+                println!("  {} ({})", uuid, count(answers, "answer"));
+            }
+        }
+
+        if !self.duplicate_uuids.is_empty() {
+            header("Cards that share a UUID:");
+            for (uuid, locations) in &self.duplicate_uuids {
+                let locations: Vec<String> = locations
+                    .iter()
+                    .map(|(card_file, line)| format!("{} line {}", card_file.display(), line))
+                    .collect();
+                println!("  {} ({})", uuid, locations.join(", "));
+            }
+        }
+
+        if !self.unknown_question_kinds.is_empty() {
+            header("Answers to questions that mathema no longer asks:");
+            for ((uuid, kind), &answers) in &self.unknown_question_kinds {
+                println!("  {} ({:?}, {})", uuid, kind, count(answers, "answer"));
+            }
+        }
+
+        if !self.unknown_languages.is_empty() {
+            header("Answers to questions in languages that the deck doesn't define:");
+            for ((uuid, kind), &answers) in &self.unknown_languages {
+                println!("  {} ({}, {})", uuid, kind, count(answers, "answer"));
+            }
+        }
+    }
+
+    /// Fixes every problem, and saves the result. Returns what was
+    /// done, like "forget 1 missing card file, delete 3 answers to
+    /// deleted cards".
+    fn repair(&self, repo: &mut MathemaRepository) -> Fallible<String> {
+        let mut done = vec![];
+
+        // The first card keeps its UUID, and with it the answers; the
+        // others get new ones, as though `mathema add` had just found
+        // them.
+        let mut renumbered_lines: BTreeMap<&Path, BTreeSet<u64>> = BTreeMap::new();
+        for locations in self.duplicate_uuids.values() {
+            for (card_file, line) in &locations[1..] {
+                renumbered_lines
+                    .entry(card_file.as_path())
+                    .or_default()
+                    .insert(*line);
+            }
+        }
+        let mut renumbered = 0;
+        for (card_file, lines) in renumbered_lines {
+            let mut cards = repo.parse_card_file_from_repo(card_file)?;
+            for card in &mut cards {
+                if lines.contains(&card.start_line) {
+                    card.uuid = Some(Uuid::fresh());
+                    renumbered += 1;
+                }
+            }
+            cards::write_cards_file(&repo.absolute_path(card_file), &cards)?;
+        }
+        if renumbered > 0 {
+            done.push(format!("renumber {}", count(renumbered, "card")));
+        }
+
+        if !self.missing_card_files.is_empty() {
+            repo.forget_card_files(&self.missing_card_files);
+            done.push(format!(
+                "forget {}",
+                count(self.missing_card_files.len(), "missing card file")
+            ));
+        }
+
+        // The journal only grows, so that copies of it merge; deleted
+        // answers stay in it, with a note that they are deleted.
+        let now = Utc::now();
+        let mut deletions = vec![];
+
+        if !self.orphaned_records.is_empty() {
+            for &uuid in self.orphaned_records.keys() {
+                deletions.push(Deletion {
+                    uuid,
+                    question_kind: None,
+                    date: now,
+                });
+            }
+            let answers: usize = self.orphaned_records.values().sum();
+            done.push(format!(
+                "delete {} to deleted cards",
+                count(answers, "answer")
+            ));
+        }
+
+        if !self.unknown_question_kinds.is_empty() {
+            for &(uuid, kind) in self.unknown_question_kinds.keys() {
+                deletions.push(Deletion {
+                    uuid,
+                    question_kind: Some(serde_json::to_value(kind)?),
+                    date: now,
+                });
+            }
+            let answers: usize = self.unknown_question_kinds.values().sum();
+            done.push(format!(
+                "delete {} to questions no longer asked",
+                count(answers, "answer")
+            ));
+        }
+
+        if !self.unknown_languages.is_empty() {
+            for (uuid, kind) in self.unknown_languages.keys() {
+                deletions.push(Deletion {
+                    uuid: *uuid,
+                    question_kind: Some(serde_json::from_str(kind)?),
+                    date: now,
+                });
+            }
+            let answers: usize = self.unknown_languages.values().sum();
+            done.push(format!(
+                "delete {} in undefined languages",
+                count(answers, "answer")
+            ));
+        }
+
+        repo.forget_answers(&deletions)?;
+
+        let summary = done.join(", ");
+        repo.write_database(&format!("fsck: {}", summary))?;
+        Ok(summary)
+    }
+}
+
+/// Like "1 answer" or "3 answers".
+fn count(n: usize, noun: &str) -> String {
+    if n == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", n, noun)
+    }
+}
+
+#[test]
+fn counts() {
+    assert_eq!(count(1, "answer"), "1 answer");
+    assert_eq!(count(3, "missing card file"), "3 missing card files");
+}
@@ -1,13 +1,35 @@
-//! Manages the git repository and files that stores our data.
+//! Manages the files that store our data, and the storage (usually a
+//! git repository) that keeps their history.
 
+use crate::config::{self, RELATIVE_CONFIG_PATH};
+use crate::db::journal::{self, Deletion, JournalEntry, UnknownAnswer};
+use crate::db::schema;
 use crate::prelude::*;
+use crate::storage::{DirectoryStorage, GitStorage, Storage, StorageKind, SyncResult};
 
 crate struct MathemaRepository {
     dry_run: bool,
     directory_path: PathBuf,
-    repository: git2::Repository,
+    storage: Box<dyn Storage>,
     database: Database,
+    config: DeckConfig,
+    user_config: UserConfig,
     cards: HashMap<Uuid, Card>,
+
+    /// Database-relative path of the card file each card was loaded from.
+    card_sources: HashMap<Uuid, PathBuf>,
+
+    /// Results of the answers recorded since the last commit, for its
+    /// message.
+    recorded_results: Vec<QuestionResult>,
+
+    /// Database-relative paths of the card files forgotten since the
+    /// last commit (see `forget_card_files`).
+    forgotten_card_files: Vec<PathBuf>,
+
+    /// Answers in the journal to questions in languages that the deck
+    /// doesn't define, for `mathema fsck`.
+    unknown_answers: Vec<UnknownAnswer>,
 }
 
 #[derive(Default)]
@@ -19,28 +41,55 @@ crate struct Status {
     crate valid_card_files: usize,
 }
 
-const RELATIVE_DB_PATH: &str = ".mathema-v1.ron";
+crate const RELATIVE_DB_PATH: &str = ".mathema-v1.ron";
+
+/// The directory holding the journal of answers (see `db::journal`).
+crate const RELATIVE_JOURNAL_PATH: &str = ".mathema-history";
 
 impl MathemaRepository {
-    crate fn create_on_disk(directory: impl AsRef<Path>) -> Fallible<MathemaRepository> {
+    crate fn create_on_disk(
+        directory: impl AsRef<Path>,
+        storage_kind: StorageKind,
+    ) -> Fallible<MathemaRepository> {
         let directory_path = directory.as_ref().to_owned();
 
         fs::create_dir(&directory_path).with_context(|_| MathemaErrorKind::CreatingDir {
             directory_path: directory_path.display().to_string(),
         })?;
 
-        let repository = git2::Repository::init(&directory_path)?;
+        let storage: Box<dyn Storage> = match storage_kind {
+            StorageKind::Git => Box::new(GitStorage::init(&directory_path)?),
+            StorageKind::Directory => {
+                // Git is the default, so the deck has to say otherwise.
+                let config_path = directory_path.join(RELATIVE_CONFIG_PATH);
+                fs::write(&config_path, "(\n    storage: Directory,\n)\n").with_context(|_| {
+                    MathemaErrorKind::AccessingFile {
+                        file: config_path.display().to_string(),
+                    }
+                })?;
+                Box::new(DirectoryStorage)
+            }
+        };
 
         let database = Database::empty();
 
         let mut repository = MathemaRepository {
             directory_path,
-            repository,
+            storage,
             database,
+            config: DeckConfig {
+                storage: storage_kind,
+                ..DeckConfig::default()
+            },
+            user_config: UserConfig::default(),
             dry_run: false,
             cards: HashMap::new(),
+            card_sources: HashMap::new(),
+            recorded_results: vec![],
+            forgotten_card_files: vec![],
+            unknown_answers: vec![],
         };
-        repository.write_database()?;
+        repository.write_database("new: create the deck")?;
 
         Ok(repository)
     }
@@ -51,27 +100,135 @@ impl MathemaRepository {
 
     crate fn open_full(dry_run: bool, directory: impl AsRef<Path>) -> Fallible<MathemaRepository> {
         let directory_path = directory.as_ref().to_owned();
+        let config = Self::load_deck_config(&directory_path)?;
+
+        // The database may refer to languages that the configuration
+        // defines, so define them first.
+        config.define_languages()?;
+
         let db_path = directory_path.join(RELATIVE_DB_PATH);
-        let database =
+        let mut database =
             Self::read_from(&db_path, |f| Database::load_from(f)).with_context(|_| {
                 MathemaErrorKind::CannotLoadDatabase {
                     database_path: db_path.display().to_string(),
                 }
             })?;
 
-        let repository = git2::Repository::open(&directory_path).with_context(|_| {
-            MathemaErrorKind::NoGitRepositoryFound {
-                directory_path: directory_path.display().to_string(),
+        // Rebuild the records from the journal. Older databases have
+        // records of their own; we move those into the journal below.
+        let journal_path = directory_path.join(RELATIVE_JOURNAL_PATH);
+        let journal =
+            journal::read(&journal_path).with_context(|_| MathemaErrorKind::AccessingFile {
+                file: journal_path.display().to_string(),
+            })?;
+        let unjournaled = journal::entries(&database);
+        database.user = User::default();
+        journal::replay(&mut database, journal.entries);
+        let unknown_answers = journal.unknown_answers;
+
+        let user_config = match config::user_config_path() {
+            Some(ref user_config_path) if user_config_path.exists() => {
+                Self::read_from(user_config_path, |f| UserConfig::load_from(f)).with_context(
+                    |_| MathemaErrorKind::CannotLoadUserConfig {
+                        config_path: user_config_path.display().to_string(),
+                    },
+                )?
             }
-        })?;
+            _ => UserConfig::default(),
+        };
+
+        let storage: Box<dyn Storage> = match config.storage {
+            StorageKind::Git => Box::new(GitStorage::open(&directory_path, dry_run)?),
+            StorageKind::Directory => Box::new(DirectoryStorage),
+        };
 
-        Ok(MathemaRepository {
+        let mut repository = MathemaRepository {
             directory_path,
-            repository,
+            storage,
             database,
+            config,
+            user_config,
             dry_run,
             cards: HashMap::new(),
-        })
+            card_sources: HashMap::new(),
+            recorded_results: vec![],
+            forgotten_card_files: vec![],
+            unknown_answers,
+        };
+
+        // In a dry run, this only puts the old records back into the
+        // database; they are moved into the store for real next time.
+        let moved = repository.record_answers(unjournaled)?;
+
+        // Notices about the deck go to stderr, out of the way of output
+        // meant for other programs, like `mathema history --csv`.
+        if !dry_run {
+            let upgraded_from = repository.database.upgraded_from;
+            if let Some(version) = upgraded_from {
+                // Keep the old database around, in case the upgrade
+                // went wrong. Git already keeps it for us.
+                let backup = match repository.config.storage {
+                    StorageKind::Git => "git's history".to_string(),
+                    StorageKind::Directory => {
+                        let backup_path = format!("{}.v{}.bak", RELATIVE_DB_PATH, version);
+                        let absolute_backup_path = repository.absolute_path(&backup_path);
+                        fs::copy(repository.db_path(), &absolute_backup_path).with_context(
+                            |_| MathemaErrorKind::AccessingFile {
+                                file: absolute_backup_path.display().to_string(),
+                            },
+                        )?;
+                        format!("`{}`", backup_path)
+                    }
+                };
+                eprintln!(
+                    "Upgraded the database from version {} to version {} (the old one is in {}).",
+                    version,
+                    schema::CURRENT_VERSION,
+                    backup
+                );
+            }
+
+            if moved > 0 {
+                eprintln!(
+                    "Moved {} answers from the database into `{}`.",
+                    moved, RELATIVE_JOURNAL_PATH
+                );
+                let message = format!(
+                    "migrate: move {} answers into `{}`",
+                    moved, RELATIVE_JOURNAL_PATH
+                );
+                repository.write_database(&message)?;
+            } else if let Some(version) = upgraded_from {
+                let message = format!(
+                    "migrate: upgrade the database from version {} to version {}",
+                    version,
+                    schema::CURRENT_VERSION
+                );
+                repository.write_database(&message)?;
+            } else if repository.storage.has_unsaved_answers()? {
+                // A quiz was interrupted before it could commit.
+                eprintln!("Committing answers from a quiz that was interrupted.");
+                repository.write_database("quiz: answers from a quiz that was interrupted")?;
+            }
+        }
+
+        Ok(repository)
+    }
+
+    /// Loads the deck configuration in `directory_path`, if there is one.
+    crate fn load_deck_config(directory_path: &Path) -> Fallible<DeckConfig> {
+        let config_path = directory_path.join(RELATIVE_CONFIG_PATH);
+        if !config_path.exists() {
+            return Ok(DeckConfig::default());
+        }
+
+        Ok(
+            Self::read_from(&config_path, |f| DeckConfig::load_from(f)).with_context(|_| {
+                MathemaErrorKind::CannotLoadConfig {
+                    config_path: config_path.display().to_string(),
+                }
+            })?,
+        )
     }
 
     crate fn database(&self) -> &Database {
@@ -82,6 +239,23 @@ impl MathemaRepository {
         &mut self.database
     }
 
+    /// Removes card files that no longer exist from the database. The
+    /// next commit records that they are gone.
+    crate fn forget_card_files(&mut self, card_files: &[PathBuf]) {
+        self.database
+            .card_files
+            .retain(|card_file| !card_files.contains(card_file));
+        self.forgotten_card_files.extend_from_slice(card_files);
+    }
+
+    crate fn config(&self) -> &DeckConfig {
+        &self.config
+    }
+
+    crate fn user_config(&self) -> &UserConfig {
+        &self.user_config
+    }
+
     crate fn cards(&self) -> &HashMap<Uuid, Card> {
         &self.cards
     }
@@ -94,8 +268,26 @@ impl MathemaRepository {
         &self.cards[&uuid]
     }
 
+    /// Returns the (database-relative) card file that `uuid` was loaded from.
+    crate fn card_file(&self, uuid: Uuid) -> &Path {
+        &self.card_sources[&uuid]
+    }
+
+    /// Returns the loaded cards from the given card file, in the order
+    /// in which they appear in the file.
+    crate fn cards_in_file(&self, card_file: &Path) -> Vec<&Card> {
+        let mut cards: Vec<&Card> = self
+            .card_sources
+            .iter()
+            .filter(|(_, source)| *source == card_file)
+            .map(|(uuid, _)| &self.cards[uuid])
+            .collect();
+        cards.sort_by_key(|card| card.start_line);
+        cards
+    }
+
     /// Makes a "database-relative" path into an absolute path.
-    fn absolute_path(&self, relative_path: impl AsRef<Path>) -> PathBuf {
+    crate fn absolute_path(&self, relative_path: impl AsRef<Path>) -> PathBuf {
         self.directory_path.join(relative_path)
     }
 
@@ -129,10 +321,6 @@ impl MathemaRepository {
         self.absolute_path(RELATIVE_DB_PATH)
     }
 
-    fn signature() -> Fallible<git2::Signature<'static>> {
-        Ok(git2::Signature::now("mathema", "mathema@example.com")?)
-    }
-
     fn write_file(
         &self,
         file_name: &Path,
@@ -152,7 +340,103 @@ impl MathemaRepository {
         Ok(r)
     }
 
-    crate fn write_database(&mut self) -> Fallible<()> {
+    /// Records an answer. It goes into the journal right away, so that
+    /// it survives even if we never get to `write_database`.
+    crate fn record_answer(
+        &mut self,
+        uuid: Uuid,
+        question_kind: QuestionKind,
+        record: QuestionRecord,
+    ) -> Fallible<()> {
+        self.record_answers(vec![JournalEntry {
+            uuid,
+            question_kind,
+            date: record.date,
+            result: record.result,
+        }])?;
+        Ok(())
+    }
+
+    /// Records a batch of answers, like `record_answer`, skipping any
+    /// that we already have. Returns how many were new.
+    crate fn record_answers(&mut self, entries: Vec<JournalEntry>) -> Fallible<usize> {
+        let entries = journal::replay(&mut self.database, entries);
+        self.recorded_results
+            .extend(entries.iter().map(|entry| entry.result));
+        if !self.dry_run {
+            let journal_path = self.absolute_path(RELATIVE_JOURNAL_PATH);
+            journal::append(&journal_path, &entries).with_context(|_| {
+                MathemaErrorKind::AccessingFile {
+                    file: journal_path.display().to_string(),
+                }
+            })?;
+        }
+        Ok(entries.len())
+    }
+
+    /// Deletes answers, and notes in the journal that they are gone
+    /// (see `journal::Deletion`).
+    crate fn forget_answers(&mut self, deletions: &[Deletion]) -> Fallible<()> {
+        for deletion in deletions {
+            if let Some(card_record) = self.database.user.records.get_mut(&deletion.uuid) {
+                for (kind, records) in &mut card_record.questions {
+                    let kind = serde_json::to_value(kind)?;
+                    records.retain(|record| !deletion.deletes(deletion.uuid, &kind, record.date));
+                }
+                card_record
+                    .questions
+                    .retain(|_, records| !records.is_empty());
+            }
+        }
+        self.unknown_answers.retain(|answer| {
+            !deletions
+                .iter()
+                .any(|deletion| deletion.deletes(answer.uuid, &answer.question_kind, answer.date))
+        });
+
+        if !self.dry_run {
+            let journal_path = self.absolute_path(RELATIVE_JOURNAL_PATH);
+            journal::append_deletions(&journal_path, deletions).with_context(|_| {
+                MathemaErrorKind::AccessingFile {
+                    file: journal_path.display().to_string(),
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    crate fn unknown_answers(&self) -> &[UnknownAnswer] {
+        &self.unknown_answers
+    }
+
+    /// Summarizes the answers recorded since the last commit, like "41
+    /// answers (35 yes / 4 almost / 2 no)".
+    crate fn answers_summary(&self) -> String {
+        let count = |result| {
+            self.recorded_results
+                .iter()
+                .filter(|&&r| r == result)
+                .count()
+        };
+        format!(
+            "{} {} ({} yes / {} almost / {} no)",
+            self.recorded_results.len(),
+            if self.recorded_results.len() == 1 {
+                "answer"
+            } else {
+                "answers"
+            },
+            count(QuestionResult::Yes),
+            count(QuestionResult::Almost),
+            count(QuestionResult::No),
+        )
+    }
+
+    /// Writes the database and saves it (usually, commits it), along
+    /// with the rest of the deck. `message` says what changed, like
+    /// "add: lesson5.cards (23 new cards)"; by convention, it starts
+    /// with the subcommand.
+    crate fn write_database(&mut self, message: &str) -> Fallible<()> {
         if !self.dry_run {
             let db_path = self.db_path();
             self.write_file(&self.db_path(), |f| self.database.write_to(f))
@@ -160,45 +444,22 @@ impl MathemaRepository {
                     file: db_path.display().to_string(),
                 })?;
 
-            self.git_commit()?;
+            self.storage.save(
+                &self.database.card_files,
+                &self.forgotten_card_files,
+                message,
+            )?;
         }
+        self.recorded_results.clear();
+        self.forgotten_card_files.clear();
 
         Ok(())
     }
 
-    /// Creates a new git commit, adding in the changes from all of
-    /// the registered `cards` files as well as the index.
-    fn git_commit(&mut self) -> Fallible<()> {
+    /// Syncs the deck with its copies elsewhere (see `Storage::sync`).
+    crate fn sync(&self) -> Fallible<SyncResult> {
         assert!(!self.dry_run);
-        let mut index = self.repository.index()?;
-        index.add_path(Path::new(RELATIVE_DB_PATH))?;
-        for card_file in &self.database.card_files {
-            index.add_path(card_file)?;
-        }
-        index.write()?;
-
-        let tree_id = self.repository.index()?.write_tree()?;
-        let tree = self.repository.find_tree(tree_id)?;
-        let signature = Self::signature()?;
-        let parents = match self.repository.head() {
-            Ok(head_ref) => {
-                let head_oid = head_ref.target().unwrap();
-                Some(self.repository.find_commit(head_oid)?)
-            }
-            _ => None,
-        };
-        let parents: Vec<_> = parents.iter().collect();
-        self.repository.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            "mathema: write_database",
-            &tree,
-            &parents,
-        )?;
-        self.repository.checkout_head(None)?;
-
-        Ok(())
+        self.storage.sync()
     }
 
     /// Returns database-relative paths to all the card files.
@@ -271,6 +532,7 @@ impl MathemaRepository {
                 if let Some(uuid) = card.uuid {
                     if !self.cards.contains_key(&uuid) {
                         self.cards.insert(uuid, card);
+                        self.card_sources.insert(uuid, card_file.clone());
                         status.valid_cards += 1;
                     } else {
                         status.duplicate_uuids.push(uuid);
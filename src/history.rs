@@ -0,0 +1,118 @@
+//! History subcommand: lists every answer ever recorded, oldest first.
+
+use crate::prelude::*;
+
+struct HistoryRow<'r> {
+    uuid: Uuid,
+    card_file: Option<&'r Path>,
+    question_kind: QuestionKind,
+    record: &'r QuestionRecord,
+
+    /// Time since the previous answer to the same question, if any.
+    interval: Option<Duration>,
+}
+
+crate fn history(options: &MathemaOptions, csv: bool) -> Fallible<()> {
+    let repo = &mut MathemaRepository::open(options)?;
+    let status = repo.load_cards()?;
+    if status.warn_if_needed(options.force) {
+        return Ok(());
+    }
+
+    let mut rows = vec![];
+    for (&uuid, card_record) in &repo.database().user.records {
+        // Records can outlive their card, if it was deleted from its file.
+        let card_file = if repo.cards().contains_key(&uuid) {
+            Some(repo.card_file(uuid))
+        } else {
+            None
+        };
+
+        for (&question_kind, records) in &card_record.questions {
+            let mut previous: Option<&QuestionRecord> = None;
+            for record in records {
+                rows.push(HistoryRow {
+                    uuid,
+                    card_file,
+                    question_kind,
+                    record,
+                    interval: previous.map(|p| record.date.signed_duration_since(p.date)),
+                });
+                previous = Some(record);
+            }
+        }
+    }
+    rows.sort_by_key(|row| (row.record.date, row.uuid, row.question_kind));
+
+    let stdout = &mut io::stdout();
+    if csv {
+        write_csv(stdout, &rows)?;
+    } else {
+        for row in &rows {
+            let text = if repo.cards().contains_key(&row.uuid) {
+                repo.card(row.uuid)
+                    .lines_with_kind(row.question_kind.prompt_line_kind())
+                    .next()
+                    .unwrap_or("")
+            } else {
+                "(deleted card)"
+            };
+            writeln!(
+                stdout,
+                "{} {:?}: {} `{}`",
+                row.record.date,
+                row.record.result,
+                row.question_kind.prompt_text(),
+                text,
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_csv(output: &mut dyn io::Write, rows: &[HistoryRow<'_>]) -> Fallible<()> {
+    writeln!(output, "uuid,file,question_kind,date,result,interval_seconds")?;
+    for row in rows {
+        let question_kind = match row.question_kind {
+            QuestionKind::Translate { from, to } => {
+                format!("translate-{}-{}", from.abbreviation(), to.abbreviation())
+            }
+        };
+        let card_file = row
+            .card_file
+            .map(|f| f.display().to_string())
+            .unwrap_or_default();
+        let interval = row
+            .interval
+            .map(|i| i.num_seconds().to_string())
+            .unwrap_or_default();
+        writeln!(
+            output,
+            "{},{},{},{},{:?},{}",
+            row.uuid,
+            csv_field(&card_file),
+            question_kind,
+            row.record.date.to_rfc3339(),
+            row.record.result,
+            interval,
+        )?;
+    }
+    Ok(())
+}
+
+/// Quotes a field if it contains anything that CSV treats specially.
+fn csv_field(text: &str) -> String {
+    if text.contains(|c: char| c == ',' || c == '"' || c == '\n') {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+#[test]
+fn csv_fields() {
+    assert_eq!(csv_field("lesson.cards"), "lesson.cards");
+    assert_eq!(csv_field("a,b.cards"), "\"a,b.cards\"");
+    assert_eq!(csv_field("a\"b.cards"), "\"a\"\"b.cards\"");
+}
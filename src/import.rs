@@ -0,0 +1,149 @@
+//! Import subcommand: rebuilds a deck from a snapshot written by
+//! `mathema export --format json` (or `yaml`).
+
+use crate::config::RELATIVE_CONFIG_PATH;
+use crate::db::journal;
+use crate::export::{
+    snapshot::{DeckSnapshot, SnapshotConfig},
+    ExportFormat,
+};
+use crate::prelude::*;
+use crate::storage::StorageKind;
+
+crate fn import(
+    options: &MathemaOptions,
+    file: &str,
+    new_directory: &str,
+    format: Option<ExportFormat>,
+) -> Fallible<()> {
+    let MathemaOptions {
+        directory,
+        force: _,
+        dry_run,
+        command: _,
+    } = options;
+
+    if *dry_run {
+        throw!(MathemaErrorKind::IncompatibleOption {
+            option: "--dry-run",
+            command: "import",
+        });
+    }
+
+    if directory.is_some() {
+        throw!(MathemaErrorKind::IncompatibleOption {
+            option: "--directory",
+            command: "import",
+        });
+    }
+
+    // We delete the directory if the import fails, so it had better
+    // be ours.
+    if Path::new(new_directory).exists() {
+        throw!(MathemaErrorKind::DirectoryExists {
+            directory_path: new_directory.to_string(),
+        });
+    }
+
+    let file = Path::new(file);
+    let format = format
+        .or_else(|| ExportFormat::from_extension(file))
+        .unwrap_or(ExportFormat::Json);
+    let input = fs::read_to_string(file).with_context(|_| MathemaErrorKind::AccessingFile {
+        file: file.display().to_string(),
+    })?;
+
+    // The cards and records may be in languages that the snapshot's
+    // config defines, so read the config and define them first.
+    let snapshot: DeckSnapshot = match format {
+        ExportFormat::Json => {
+            let SnapshotConfig { config } = serde_json::from_str(&input)?;
+            config.define_languages()?;
+            serde_json::from_str(&input)?
+        }
+        ExportFormat::Yaml => {
+            let SnapshotConfig { config } = serde_yaml::from_str(&input)?;
+            config.define_languages()?;
+            serde_yaml::from_str(&input)?
+        }
+        ExportFormat::AnkiTsv => throw!(MathemaErrorKind::CannotImportFormat {
+            format: format.name(),
+        }),
+    };
+
+    match import_atomic(new_directory, &snapshot) {
+        Ok(()) => Ok(()),
+        Err(e) => {
+            let _ = fs::remove_dir_all(&new_directory); // if this fails, oh well
+            Err(e)
+        }
+    }
+}
+
+fn import_atomic(directory: impl AsRef<Path>, snapshot: &DeckSnapshot) -> Fallible<()> {
+    let directory = directory.as_ref();
+    let mut repo = MathemaRepository::create_on_disk(directory, StorageKind::Git)?;
+
+    // Keep the languages and articles. The new deck is in git, wherever
+    // the snapshot came from.
+    let config = &snapshot.config;
+    if !config.languages.is_empty() || !config.articles.is_empty() {
+        let config = DeckConfig {
+            storage: StorageKind::Git,
+            ..config.clone()
+        };
+        let config_path = directory.join(RELATIVE_CONFIG_PATH);
+        let result: Fallible<()> = try {
+            let text = ::ron::ser::to_string_pretty(&config, Default::default())?;
+            fs::write(&config_path, text)?;
+        };
+        result.with_context(|_| MathemaErrorKind::AccessingFile {
+            file: config_path.display().to_string(),
+        })?;
+    }
+
+    for card_file in &snapshot.card_files {
+        // The snapshot may have come from anywhere, so don't let it
+        // write outside of the new deck.
+        let is_relative = card_file.path.components().all(|c| match c {
+            path::Component::Normal(_) => true,
+            _ => false,
+        });
+        if !is_relative {
+            throw!(MathemaErrorKind::NotInRepo {
+                file: card_file.path.display().to_string(),
+            });
+        }
+
+        let path = directory.join(&card_file.path);
+        let result: Fallible<()> = try {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            let mut file = File::create(&path)?;
+            cards::write_cards_to(&mut file, &card_file.cards)?;
+        };
+        result.with_context(|_| MathemaErrorKind::AccessingFile {
+            file: path.display().to_string(),
+        })?;
+    }
+
+    let database = snapshot.database();
+    repo.database_mut().card_files = database.card_files.clone();
+    repo.record_answers(journal::entries(&database))?;
+    let message = format!(
+        "import: {} card files, {}",
+        snapshot.card_files.len(),
+        repo.answers_summary()
+    );
+    repo.write_database(&message)?;
+
+    println!(
+        "Imported {} card files and the history of {} cards into `{}`.",
+        snapshot.card_files.len(),
+        snapshot.records.len(),
+        directory.display(),
+    );
+
+    Ok(())
+}
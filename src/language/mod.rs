@@ -0,0 +1,409 @@
+//! Languages. A few are built in, and a deck can define more in its
+//! configuration (see `config::LanguageConfig`). Either way, a
+//! `Language` is just a handle on its definition, named by the id we
+//! store in the database (e.g., `Greek`).
+
+use crate::prelude::*;
+use std::borrow::Borrow;
+use std::sync::RwLock;
+
+crate mod romanization;
+crate mod transliteration;
+
+crate use self::romanization::RomanizationScheme;
+crate use self::transliteration::{
+    InputMethod, TransliterationScheme, TransliterationTable, Transliterator,
+};
+
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) struct Language {
+    id: &'static str,
+}
+
+/// Everything we know about a language.
+crate struct LanguageDefinition {
+    /// The name we store in the database, e.g. `Greek`. It must never
+    /// change, or we lose track of the user's history.
+    crate id: &'static str,
+
+    /// The code used in card files and on the command line, e.g. `gr`.
+    crate code: &'static str,
+
+    /// The name of the language in the language itself, e.g. `Ελληνικά`.
+    crate name: &'static str,
+
+    /// How the user types the language.
+    crate input_method: InputMethod,
+
+    /// Articles that may start a noun phrase in this language.
+    crate articles: &'static [&'static str],
+
+    /// How to normalize responses before checking them.
+    crate normalization: &'static [Normalization],
+}
+
+static BUILT_IN_LANGUAGES: &[LanguageDefinition] = &[
+    LanguageDefinition {
+        id: "English",
+        code: "en",
+        name: "English",
+        input_method: InputMethod::Latin,
+        articles: &["the", "a", "an"],
+        normalization: &[],
+    },
+    LanguageDefinition {
+        id: "Greek",
+        code: "gr",
+        name: "Ελληνικά",
+        input_method: InputMethod::Greek,
+        articles: &[
+            "ο", "η", "το", "οι", "τα", "τον", "την", "του", "της", "των", "τους", "τις",
+        ],
+        normalization: &[],
+    },
+    LanguageDefinition {
+        id: "AncientGreek",
+        code: "grc",
+        name: "Ἀρχαία Ἑλληνικά",
+        input_method: InputMethod::AncientGreek,
+        articles: &[
+            "ὁ", "ἡ", "τό", "οἱ", "αἱ", "τά", "τόν", "τήν", "τοῦ", "τῆς", "τῷ", "τῇ", "τῶν",
+            "τοῖς", "ταῖς", "τούς", "τάς",
+        ],
+        normalization: &[],
+    },
+    LanguageDefinition {
+        id: "Russian",
+        code: "ru",
+        name: "Русский",
+        input_method: InputMethod::Russian,
+        articles: &[],
+        normalization: &[Normalization::IgnoreStress],
+    },
+];
+
+lazy_static! {
+    /// Every language we know about, built-in ones first.
+    static ref LANGUAGES: RwLock<Vec<&'static LanguageDefinition>> =
+        RwLock::new(BUILT_IN_LANGUAGES.iter().collect());
+}
+
+#[allow(non_upper_case_globals)]
+impl Language {
+    crate const English: Language = Language { id: "English" };
+    crate const Greek: Language = Language { id: "Greek" };
+    crate const AncientGreek: Language = Language { id: "AncientGreek" };
+    crate const Russian: Language = Language { id: "Russian" };
+}
+
+impl Language {
+    /// Every language we know about, built-in ones first.
+    crate fn all() -> Vec<Language> {
+        LANGUAGES
+            .read()
+            .unwrap()
+            .iter()
+            .map(|definition| Language { id: definition.id })
+            .collect()
+    }
+
+    /// Adds a language, replacing any earlier definition with the same id.
+    crate fn define(definition: LanguageDefinition) -> Fallible<Language> {
+        let mut languages = LANGUAGES.write().unwrap();
+        if let Some(other) = languages
+            .iter()
+            .find(|other| other.code == definition.code && other.id != definition.id)
+        {
+            throw!(MathemaErrorKind::LanguageCodeInUse {
+                code: definition.code.to_string(),
+                language: other.id.to_string(),
+            });
+        }
+
+        let id = definition.id;
+        let definition: &'static LanguageDefinition = Box::leak(Box::new(definition));
+        match languages.iter().position(|other| other.id == id) {
+            Some(index) => languages[index] = definition,
+            None => languages.push(definition),
+        }
+        Ok(Language { id })
+    }
+
+    /// The language with the given id, as stored in the database, if
+    /// it is built in or the deck defines it.
+    crate fn from_id(id: &str) -> Option<Language> {
+        LANGUAGES
+            .read()
+            .unwrap()
+            .iter()
+            .find(|definition| definition.id == id)
+            .map(|definition| Language { id: definition.id })
+    }
+
+    /// The name we store in the database, e.g. `AncientGreek`.
+    crate fn id(self) -> &'static str {
+        self.id
+    }
+
+    fn definition(self) -> &'static LanguageDefinition {
+        LANGUAGES
+            .read()
+            .unwrap()
+            .iter()
+            .cloned()
+            .find(|definition| definition.id == self.id)
+            .expect("every language is defined when it is created")
+    }
+
+    crate fn abbreviation(self) -> &'static str {
+        self.definition().code
+    }
+
+    crate fn full_name(self) -> &'static str {
+        self.definition().name
+    }
+
+    crate fn input_method(self) -> InputMethod {
+        self.definition().input_method
+    }
+
+    /// Articles that may start a noun phrase in this language.
+    crate fn articles(self) -> &'static [&'static str] {
+        self.definition().articles
+    }
+
+    crate fn normalization(self) -> &'static [Normalization] {
+        self.definition().normalization
+    }
+}
+
+/// Definitions live as long as the program, so their strings do too.
+crate fn leak_str(s: String) -> &'static str {
+    Box::leak(s.into_boxed_str())
+}
+
+impl fmt::Debug for Language {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "{}", self.id)
+    }
+}
+
+impl FromStr for Language {
+    type Err = MathemaError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LANGUAGES
+            .read()
+            .unwrap()
+            .iter()
+            .find(|definition| definition.code == s || definition.id == s)
+            .map(|definition| Language { id: definition.id })
+            .ok_or_else(|| {
+                MathemaErrorKind::UnrecognizedLanguage {
+                    text: s.to_string(),
+                }
+                .into()
+            })
+    }
+}
+
+// `Language` used to be an enum, so we (de)serialize it as if it were
+// one. That keeps existing databases, which say things like
+// `Translate(from: English, to: Greek)`, readable.
+
+impl serde::Serialize for Language {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_unit_variant("Language", 0, self.id)
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for Language {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let LanguageId(id) = LanguageId::deserialize(deserializer)?;
+        Language::from_id(&id).ok_or_else(|| {
+            serde::de::Error::custom(MathemaErrorKind::UnrecognizedLanguage { text: id })
+        })
+    }
+}
+
+/// A language's id, read the way we read a `Language`, but before we
+/// know whether it is defined. The deck config needs these, as it
+/// mentions its own languages before they are defined.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+crate struct LanguageId(crate String);
+
+impl Borrow<str> for LanguageId {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+impl serde::Serialize for LanguageId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        // Write it as we write a `Language` where we can: RON can't
+        // read a string where it expects a variant.
+        match Language::from_id(&self.0) {
+            Some(language) => serde::Serialize::serialize(&language, serializer),
+            None => serializer.serialize_str(&self.0),
+        }
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for LanguageId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_enum("Language", &[], LanguageIdVisitor)
+    }
+}
+
+/// The name of a variant, which some formats (like RON) write as a bare
+/// identifier rather than a string.
+struct LanguageVariant(LanguageId);
+
+impl<'de> serde::Deserialize<'de> for LanguageVariant {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Ok(LanguageVariant(
+            deserializer.deserialize_identifier(LanguageIdVisitor)?,
+        ))
+    }
+}
+
+struct LanguageIdVisitor;
+
+impl<'de> serde::de::Visitor<'de> for LanguageIdVisitor {
+    type Value = LanguageId;
+
+    fn expecting(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(fmt, "a language")
+    }
+
+    fn visit_str<E: serde::de::Error>(self, id: &str) -> Result<LanguageId, E> {
+        Ok(LanguageId(id.to_string()))
+    }
+
+    /// RON hands us identifiers as bytes.
+    fn visit_bytes<E: serde::de::Error>(self, id: &[u8]) -> Result<LanguageId, E> {
+        let id = std::str::from_utf8(id)
+            .map_err(|_| E::invalid_value(serde::de::Unexpected::Bytes(id), &self))?;
+        self.visit_str(id)
+    }
+
+    fn visit_enum<A: serde::de::EnumAccess<'de>>(self, data: A) -> Result<LanguageId, A::Error> {
+        use serde::de::VariantAccess;
+        let (LanguageVariant(id), variant) = data.variant()?;
+        variant.unit_variant()?;
+        Ok(id)
+    }
+}
+
+impl Language {
+    /// Transliterates `input` with mathema's own scheme, as we do for
+    /// card files. Unlike what the user types, card files have no
+    /// escapes.
+    crate fn transliterate(self, input: &str) -> String {
+        let mut result = String::new();
+        for c in input.chars() {
+            self.push_char(c, &mut result);
+        }
+        result
+    }
+
+    /// Transliterates what the user typed with `scheme`, unless it is
+    /// already in this language's own script (see `Transliterator`).
+    crate fn transliterate_with(self, scheme: TransliterationScheme, input: &str) -> String {
+        let mut transliterator = Transliterator::for_input(self, scheme, input);
+        let mut result = String::new();
+        for c in input.chars() {
+            transliterator.push(c, &mut result);
+        }
+        result
+    }
+
+    crate fn push_char(self, c: char, s: &mut String) {
+        self.push_char_with(TransliterationScheme::default(), c, s)
+    }
+
+    crate fn push_char_with(self, scheme: TransliterationScheme, c: char, s: &mut String) {
+        scheme.push_char(self, c, s)
+    }
+
+    /// Writes `text` in Latin letters, unless it already is.
+    crate fn romanize(self, scheme: RomanizationScheme, text: &str) -> Option<String> {
+        scheme.romanize(self, text)
+    }
+}
+
+#[test]
+fn serialize_like_an_enum() {
+    assert_eq!(
+        serde_json::to_string(&Language::Greek).unwrap(),
+        "\"Greek\""
+    );
+    let language: Language = serde_json::from_str("\"AncientGreek\"").unwrap();
+    assert_eq!(language, Language::AncientGreek);
+
+    let question_kind: QuestionKind =
+        ::ron::de::from_str("Translate(from: English, to: Greek)").unwrap();
+    assert_eq!(
+        question_kind,
+        QuestionKind::Translate {
+            from: Language::English,
+            to: Language::Greek,
+        }
+    );
+}
+
+#[test]
+fn parse_codes_and_ids() {
+    assert_eq!(Language::from_str("gr").unwrap(), Language::Greek);
+    assert_eq!(Language::from_str("Greek").unwrap(), Language::Greek);
+    assert_eq!(Language::from_str("en").unwrap(), Language::English);
+    assert!(Language::from_str("xx").is_err());
+}
+
+#[test]
+fn define_language() {
+    let language = Language::define(LanguageDefinition {
+        id: "DefineLanguageTest",
+        code: "dlt",
+        name: "Testish",
+        input_method: InputMethod::Latin,
+        articles: &[],
+        normalization: &[],
+    })
+    .unwrap();
+    assert_eq!(Language::from_str("dlt").unwrap(), language);
+    assert_eq!(language.full_name(), "Testish");
+    assert!(Language::all().contains(&language));
+
+    // Codes must be unique.
+    assert!(Language::define(LanguageDefinition {
+        id: "NotGreek",
+        code: "gr",
+        name: "Not Greek",
+        input_method: InputMethod::Latin,
+        articles: &[],
+        normalization: &[],
+    })
+    .is_err());
+}
+
+#[test]
+fn unknown_ids_are_rejected() {
+    assert!(serde_json::from_str::<Language>("\"Klingon\"").is_err());
+    assert!(::ron::de::from_str::<QuestionKind>("Translate(from: Klingon, to: Greek)").is_err());
+
+    let id: LanguageId = serde_json::from_str("\"Klingon\"").unwrap();
+    assert_eq!(id, LanguageId("Klingon".to_string()));
+}
+
+#[test]
+fn ids_round_trip_through_ron() {
+    let greek = Language::from_id("Greek").unwrap();
+    let text = ::ron::ser::to_string(&greek).unwrap();
+    assert_eq!(text, "Greek");
+    assert_eq!(::ron::de::from_str::<Language>(&text).unwrap(), greek);
+
+    let id = LanguageId("Greek".to_string());
+    assert_eq!(::ron::ser::to_string(&id).unwrap(), "Greek");
+    let id: LanguageId = ::ron::de::from_str("Klingon").unwrap();
+    assert_eq!(id, LanguageId("Klingon".to_string()));
+}
@@ -0,0 +1,283 @@
+//! Writing Greek in Latin letters, for those who can't read it fluently
+//! yet. This is the reverse of transliteration, and only for display:
+//! we show the romanization next to the Greek, never instead of it.
+
+use super::transliteration::beta_code_mark;
+use crate::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+crate enum RomanizationScheme {
+    /// ISO 843 transliteration: `το μάθημα` is `to máthīma`. Ancient
+    /// Greek gets an `h` for the rough breathing, as in `ho lógos`.
+    Iso843,
+
+    /// What you would type in mathema's own scheme: `το μάθημα` is
+    /// `to m;auhma`.
+    Mathema,
+}
+
+impl Default for RomanizationScheme {
+    fn default() -> Self {
+        RomanizationScheme::Iso843
+    }
+}
+
+impl RomanizationScheme {
+    /// Romanizes `text`, or returns `None` if `language` is already
+    /// written in Latin letters (or we don't know how to romanize it).
+    crate fn romanize(self, language: Language, text: &str) -> Option<String> {
+        let letters = letters(text);
+        let romanized = match (language.input_method(), self) {
+            (InputMethod::Greek, RomanizationScheme::Iso843)
+            | (InputMethod::AncientGreek, RomanizationScheme::Iso843) => iso_843(&letters),
+            (InputMethod::Greek, RomanizationScheme::Mathema) => mathema(&letters, monotonic_key),
+            (InputMethod::AncientGreek, RomanizationScheme::Mathema) => {
+                mathema(&letters, polytonic_key)
+            }
+            _ => return None,
+        };
+        Some(romanized.nfc().collect())
+    }
+}
+
+/// A character and the combining marks on it.
+struct Letter {
+    base: char,
+    marks: Vec<char>,
+}
+
+impl Letter {
+    fn lower(&self) -> char {
+        self.base.to_lowercase().next().unwrap()
+    }
+}
+
+fn letters(text: &str) -> Vec<Letter> {
+    let mut letters: Vec<Letter> = vec![];
+    for c in text.nfd() {
+        match letters.last_mut() {
+            Some(letter) if is_combining_mark(c) => letter.marks.push(c),
+            _ => letters.push(Letter {
+                base: c,
+                marks: vec![],
+            }),
+        }
+    }
+    letters
+}
+
+fn is_combining_mark(c: char) -> bool {
+    c >= '\u{300}' && c <= '\u{36f}'
+}
+
+fn iso_843(letters: &[Letter]) -> String {
+    let mut s = String::new();
+
+    // Where the current word starts in `s`, for the rough breathing.
+    let mut word_start = 0;
+
+    for (index, letter) in letters.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|i| letters[i].lower());
+        let next = letters.get(index + 1);
+        let roman = match letter.lower() {
+            'α' => "a",
+            'β' => "v",
+            'γ' => match next.map(Letter::lower) {
+                Some('γ') | Some('ξ') | Some('χ') => "n",
+                _ => "g",
+            },
+            'δ' => "d",
+            'ε' => "e",
+            'ζ' => "z",
+            'η' => "ī",
+            'θ' => "th",
+            'ι' => "i",
+            'κ' => "k",
+            'λ' => "l",
+            'μ' => "m",
+            'ν' => "n",
+            'ξ' => "x",
+            'ο' => "o",
+            'π' => "p",
+            'ρ' => "r",
+            'σ' | 'ς' => "s",
+            'τ' => "t",
+            'υ' => match previous {
+                // A diaeresis splits the diphthong, as in `Ταΰγετος`.
+                Some('α') | Some('ε') | Some('η') | Some('ο')
+                    if !letter.marks.contains(&'\u{308}') =>
+                {
+                    "u"
+                }
+                _ => "y",
+            },
+            'φ' => "f",
+            'χ' => "ch",
+            'ψ' => "ps",
+            'ω' => "ō",
+            _ => {
+                s.push(letter.base);
+                s.extend(&letter.marks);
+                word_start = s.len();
+                continue;
+            }
+        };
+
+        if !letter.base.is_uppercase() {
+            s.push_str(roman);
+        } else if next.map_or(false, |next| next.base.is_uppercase()) {
+            s.push_str(&roman.to_uppercase());
+        } else {
+            let mut chars = roman.chars();
+            s.extend(chars.next().unwrap().to_uppercase());
+            s.extend(chars);
+        }
+
+        for &mark in &letter.marks {
+            match mark {
+                '\u{314}' if letter.lower() == 'ρ' => s.push('h'),
+                '\u{314}' => {
+                    // The rough breathing is written on the first vowel
+                    // (or the second of a diphthong, as in `οἱ`), but is
+                    // pronounced before the word.
+                    let word = s.split_off(word_start);
+                    let mut chars = word.chars();
+                    match chars.next() {
+                        Some(first) if first.is_uppercase() => {
+                            s.push('H');
+                            s.extend(first.to_lowercase());
+                        }
+                        Some(first) => {
+                            s.push('h');
+                            s.push(first);
+                        }
+                        None => {}
+                    }
+                    s.extend(chars);
+                }
+                '\u{313}' => {}
+                '\u{342}' => s.push('\u{302}'),
+                '\u{345}' => s.push('i'),
+                _ => s.push(mark),
+            }
+        }
+    }
+    s
+}
+
+/// Romanizes with mathema's own scheme, using `key` to find the dead
+/// key for each mark.
+fn mathema(letters: &[Letter], key: fn(char) -> Option<char>) -> String {
+    let mut s = String::new();
+    for letter in letters {
+        let roman = match letter.lower() {
+            'α' => 'a',
+            'β' => 'b',
+            'γ' => 'g',
+            'δ' => 'd',
+            'ε' => 'e',
+            'ζ' => 'z',
+            'η' => 'h',
+            'θ' => 'u',
+            'ι' => 'i',
+            'κ' => 'k',
+            'λ' => 'l',
+            'μ' => 'm',
+            'ν' => 'n',
+            'ξ' => 'j',
+            'ο' => 'o',
+            'π' => 'p',
+            'ρ' => 'r',
+            'σ' => 's',
+            'ς' => 'w',
+            'τ' => 't',
+            'υ' => 'y',
+            'φ' => 'f',
+            'χ' => 'x',
+            'ψ' => 'c',
+            'ω' => 'v',
+            ';' => 'q',
+            _ => {
+                s.push(letter.base);
+                s.extend(&letter.marks);
+                continue;
+            }
+        };
+
+        // Dead keys come before the letter.
+        s.extend(letter.marks.iter().filter_map(|&mark| key(mark)));
+        if letter.base.is_uppercase() {
+            s.push(roman.to_ascii_uppercase());
+        } else {
+            s.push(roman);
+        }
+    }
+    s
+}
+
+fn monotonic_key(mark: char) -> Option<char> {
+    match mark {
+        '\u{301}' => Some(';'),
+        '\u{308}' => Some(':'),
+        _ => None,
+    }
+}
+
+fn polytonic_key(mark: char) -> Option<char> {
+    ")(/\\=+|"
+        .chars()
+        .find(|&key| beta_code_mark(key) == Some(mark))
+}
+
+#[test]
+fn iso_843_modern() {
+    let romanize = |text| RomanizationScheme::Iso843.romanize(Language::Greek, text);
+    assert_eq!(romanize("το μάθημα").unwrap(), "to máthīma");
+    assert_eq!(romanize("ευχαριστώ").unwrap(), "eucharistṓ");
+    assert_eq!(romanize("άγγελος").unwrap(), "ángelos");
+    assert_eq!(romanize("Θεσσαλονίκη").unwrap(), "Thessaloníkī");
+    assert_eq!(romanize("ΘΕΣΣΑΛΟΝΊΚΗ").unwrap(), "THESSALONÍKĪ");
+    assert_eq!(romanize("Ταΰγετος").unwrap(), "Ta\u{ff}\u{301}getos");
+}
+
+#[test]
+fn iso_843_polytonic() {
+    let romanize = |text| RomanizationScheme::Iso843.romanize(Language::AncientGreek, text);
+    assert_eq!(romanize("ὁ λόγος").unwrap(), "ho lógos");
+    assert_eq!(romanize("Ὁ λόγος").unwrap(), "Ho lógos");
+    assert_eq!(romanize("οἱ ἄνθρωποι").unwrap(), "hoi ánthrōpoi");
+    assert_eq!(romanize("ῥήτωρ").unwrap(), "rh\u{12b}\u{301}t\u{14d}r");
+    assert_eq!(romanize("ἐν ἀρχῇ").unwrap(), "en arch\u{12b}\u{302}i");
+}
+
+#[test]
+fn mathema_round_trips() {
+    for &(language, text) in &[
+        (Language::Greek, "το μάθημα"),
+        (Language::Greek, "θροΐζω"),
+        (Language::Greek, "Ψυχή;"),
+        (Language::AncientGreek, "ὁ λόγος"),
+        (Language::AncientGreek, "ἐν ἀρχῇ ἦν"),
+    ] {
+        let romanized = RomanizationScheme::Mathema
+            .romanize(language, text)
+            .unwrap();
+        assert_eq!(language.transliterate(&romanized), text);
+    }
+
+    assert_eq!(
+        RomanizationScheme::Mathema
+            .romanize(Language::Greek, "το μάθημα")
+            .unwrap(),
+        "to m;auhma"
+    );
+}
+
+#[test]
+fn latin_languages_are_not_romanized() {
+    assert_eq!(
+        RomanizationScheme::Iso843.romanize(Language::English, "the lesson"),
+        None
+    );
+}
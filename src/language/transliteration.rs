@@ -0,0 +1,898 @@
+//! The ways of typing non-Latin languages on a Latin keyboard. Each
+//! works one character at a time, so that it can transliterate as the
+//! user types (see `Transliterator`). Greek can be typed in several
+//! schemes, which the user chooses between; other languages have just
+//! the one way.
+
+use crate::prelude::*;
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+crate enum TransliterationScheme {
+    /// mathema's own scheme: `u` for `θ`, `;` before a vowel for an
+    /// accent, and so forth.
+    Mathema,
+
+    /// TLG Beta Code: `q` for `θ`, breathings and accents after the
+    /// letter, and `*` before a capital.
+    BetaCode,
+
+    /// Greeklish as commonly written: `th` for `θ`, `ps` for `ψ`, and
+    /// `ch` or `x` for `χ`.
+    Greeklish,
+}
+
+impl Default for TransliterationScheme {
+    fn default() -> Self {
+        TransliterationScheme::Mathema
+    }
+}
+
+/// How a language is typed.
+#[derive(Copy, Clone)]
+crate enum InputMethod {
+    /// As is.
+    Latin,
+
+    /// Modern Greek, in the user's scheme.
+    Greek,
+
+    /// Polytonic Greek, in the user's scheme.
+    AncientGreek,
+
+    /// Russian, phonetically.
+    Russian,
+
+    /// With a table from the deck configuration.
+    Table(&'static TransliterationTable),
+}
+
+impl TransliterationScheme {
+    crate fn push_char(self, language: Language, c: char, s: &mut String) {
+        match (language.input_method(), self) {
+            (InputMethod::Latin, _) => s.push(c),
+            (InputMethod::Table(table), _) => table.push_char(c, s),
+            (InputMethod::Russian, _) => push_ru_char(c, s),
+            (InputMethod::Greek, TransliterationScheme::Mathema) => push_gr_char(c, s),
+            (InputMethod::AncientGreek, TransliterationScheme::Mathema) => push_grc_char(c, s),
+            (_, TransliterationScheme::BetaCode) => push_beta_code_char(c, s),
+            (_, TransliterationScheme::Greeklish) => push_greeklish_char(c, s),
+        }
+    }
+}
+
+impl InputMethod {
+    /// Is `c` in the language's own script, so that the user must have
+    /// typed it with a native keyboard layout?
+    fn is_native(self, c: char) -> bool {
+        match self {
+            InputMethod::Latin => false,
+            InputMethod::Greek | InputMethod::AncientGreek => {
+                (c >= '\u{370}' && c <= '\u{3ff}') || (c >= '\u{1f00}' && c <= '\u{1fff}')
+            }
+            InputMethod::Russian => c >= '\u{400}' && c <= '\u{4ff}',
+            InputMethod::Table(_) => c.is_alphabetic() && !c.is_ascii(),
+        }
+    }
+}
+
+/// Typed before a key to take it literally. No scheme uses it for
+/// anything else. Languages typed as is have no use for it, so there
+/// it is just a backtick.
+crate const ESCAPE: char = '`';
+
+/// Transliterates what the user types, a key at a time. Besides
+/// applying the scheme, it takes the key after an `ESCAPE` literally,
+/// and once the user types something in the language's own script
+/// (say, with a Greek keyboard layout), it leaves the rest alone too.
+crate struct Transliterator {
+    language: Language,
+    scheme: TransliterationScheme,
+
+    /// True if the last key was `ESCAPE`.
+    escaped: bool,
+
+    /// True once the user has typed in the language's own script.
+    native: bool,
+
+    /// The length of the output that the scheme may no longer change,
+    /// because it ends in a literal key. Otherwise a literal `;`
+    /// would accent the vowel after it.
+    fixed: usize,
+}
+
+impl Transliterator {
+    crate fn new(language: Language, scheme: TransliterationScheme) -> Self {
+        Transliterator {
+            language,
+            scheme,
+            escaped: false,
+            native: false,
+            fixed: 0,
+        }
+    }
+
+    /// A transliterator for all of `input` at once. If any of `input`
+    /// is in the language's own script, all of it is left alone.
+    crate fn for_input(language: Language, scheme: TransliterationScheme, input: &str) -> Self {
+        let mut transliterator = Transliterator::new(language, scheme);
+        let input_method = language.input_method();
+        transliterator.native = input.chars().any(|c| input_method.is_native(c));
+        transliterator
+    }
+
+    crate fn push(&mut self, c: char, s: &mut String) {
+        if self.escaped {
+            self.escaped = false;
+            s.push(c);
+            self.fixed = s.len();
+        } else if c == ESCAPE && self.escapes() {
+            self.escaped = true;
+        } else if self.native || self.language.input_method().is_native(c) {
+            self.native = true;
+            s.push(c);
+        } else {
+            let mut tail = s.split_off(self.fixed);
+            self.language.push_char_with(self.scheme, c, &mut tail);
+            s.push_str(&tail);
+        }
+    }
+
+    fn escapes(&self) -> bool {
+        match self.language.input_method() {
+            InputMethod::Latin => false,
+            _ => true,
+        }
+    }
+
+    /// Deletes the last character of `s`, as for backspace.
+    crate fn pop(&mut self, s: &mut String) {
+        s.pop();
+        self.fixed = self.fixed.min(s.len());
+        if s.is_empty() {
+            self.escaped = false;
+            self.native = false;
+        }
+    }
+}
+
+/// A transliteration given as a table from Latin letters, or groups
+/// of them (like `zh`), to what they stand for. Groups work like the
+/// digraphs in `push_greeklish_char`: the last letter of a group
+/// replaces whatever the letters before it were transliterated as.
+crate struct TransliterationTable {
+    /// Lower-case, and longest first, so that we prefer `shch` to `ch`.
+    entries: Vec<(String, String)>,
+}
+
+impl TransliterationTable {
+    crate fn new(table: &HashMap<String, String>) -> Self {
+        let mut entries: Vec<(String, String)> = table
+            .iter()
+            .filter(|(latin, _)| !latin.is_empty())
+            .map(|(latin, native)| (latin.to_lowercase(), native.clone()))
+            .collect();
+        entries.sort_by(|(a, _), (b, _)| b.chars().count().cmp(&a.chars().count()).then(a.cmp(b)));
+        TransliterationTable { entries }
+    }
+
+    fn transliterate(&self, input: &str) -> String {
+        let mut result = String::new();
+        for c in input.chars() {
+            self.push_char(c, &mut result);
+        }
+        result
+    }
+
+    crate fn push_char(&self, c: char, s: &mut String) {
+        let lower: String = c.to_lowercase().collect();
+        for (latin, native) in &self.entries {
+            if !latin.ends_with(&lower[..]) {
+                continue;
+            }
+
+            let prefix = &latin[..latin.len() - lower.len()];
+            if prefix.is_empty() {
+                s.push_str(&match_case(native, c.is_uppercase()));
+                return;
+            }
+
+            // Is this the end of a group? If so, `s` ends with whatever
+            // the rest of the group was transliterated as.
+            let typed = self.transliterate(prefix);
+            let typed_len = typed.chars().count();
+            if typed_len == 0 {
+                continue;
+            }
+            let start = match s.char_indices().rev().nth(typed_len - 1) {
+                Some((start, _)) => start,
+                None => continue,
+            };
+            if s[start..].to_lowercase() == typed.to_lowercase() {
+                let uppercase = s[start..].chars().next().unwrap().is_uppercase();
+                s.truncate(start);
+                s.push_str(&match_case(native, uppercase));
+                return;
+            }
+        }
+
+        s.push(c);
+    }
+}
+
+/// Capitalizes `text` if `uppercase` is true.
+fn match_case(text: &str, uppercase: bool) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) if uppercase => first.to_uppercase().chain(chars).collect(),
+        _ => text.to_string(),
+    }
+}
+
+fn push_gr_char(c: char, s: &mut String) {
+    // Adjustments:
+    // q = ;
+    // Q = :
+    // W = ; + : accent
+
+    let mut push = |if_neither: char, if_semi: char, if_colon: char, if_both: char| {
+        let mut semi = false;
+        let mut colon = false;
+        if s.ends_with(":;") {
+            semi = true;
+            colon = true;
+        } else if s.ends_with(";:") {
+            semi = true;
+            colon = true;
+        } else if s.ends_with(":") {
+            colon = true;
+        } else if s.ends_with(";") {
+            semi = true;
+        }
+
+        let (modifiers, modified) = if semi && colon && if_neither != if_both {
+            (2, if_both)
+        } else if semi && if_neither != if_semi {
+            (1, if_semi)
+        } else if colon && if_neither != if_colon {
+            (1, if_colon)
+        } else {
+            (0, if_neither)
+        };
+
+        s.truncate(s.len() - modifiers);
+        s.push(modified);
+    };
+
+    match c {
+        'a' => push('α', 'ά', 'α', 'α'),
+        'b' => push('β', 'β', 'β', 'β'),
+        'g' => push('γ', 'γ', 'γ', 'γ'),
+        'd' => push('δ', 'δ', 'δ', 'δ'),
+        'e' => push('ε', 'έ', 'ε', 'ε'),
+        'z' => push('ζ', 'ζ', 'ζ', 'ζ'),
+        'h' => push('η', 'ή', 'η', 'η'),
+        'u' => push('θ', 'θ', 'θ', 'θ'),
+        'i' => push('ι', 'ί', 'ϊ', 'ΐ'),
+        'k' => push('κ', 'κ', 'κ', 'κ'),
+        'l' => push('λ', 'λ', 'λ', 'λ'),
+        'm' => push('μ', 'μ', 'μ', 'μ'),
+        'n' => push('ν', 'ν', 'ν', 'ν'),
+        'j' => push('ξ', 'ξ', 'ξ', 'ξ'),
+        'o' => push('ο', 'ό', 'ο', 'ο'),
+        'p' => push('π', 'π', 'π', 'π'),
+        'r' => push('ρ', 'ρ', 'ρ', 'ρ'),
+        's' => push('σ', 'σ', 'σ', 'σ'),
+        't' => push('τ', 'τ', 'τ', 'τ'),
+        'y' => push('υ', 'ύ', 'υ', 'υ'),
+        'f' => push('φ', 'φ', 'φ', 'φ'),
+        'x' => push('χ', 'χ', 'χ', 'χ'),
+        'c' => push('ψ', 'ψ', 'ψ', 'ψ'),
+        'v' => push('ω', 'ώ', 'ω', 'ω'),
+        'w' => push('ς', 'ς', 'ς', 'ς'),
+        'q' => push(';', ';', ';', ';'),
+
+        'A' => push('Α', 'Ά', 'Α', 'Α'),
+        'B' => push('Β', 'Β', 'Β', 'Β'),
+        'G' => push('Γ', 'Γ', 'Γ', 'Γ'),
+        'D' => push('Δ', 'Δ', 'Δ', 'Δ'),
+        'E' => push('Ε', 'Έ', 'Ε', 'Ε'),
+        'Z' => push('Ζ', 'Ζ', 'Ζ', 'Ζ'),
+        'H' => push('Η', 'Ή', 'Η', 'Η'),
+        'U' => push('Θ', 'Θ', 'Θ', 'Θ'),
+        'I' => push('Ι', 'Ί', 'Ϊ', 'Ι'),
+        'K' => push('Κ', 'Κ', 'Κ', 'Κ'),
+        'L' => push('Λ', 'Λ', 'Λ', 'Λ'),
+        'M' => push('Μ', 'Μ', 'Μ', 'Μ'),
+        'N' => push('Ν', 'Ν', 'Ν', 'Ν'),
+        'J' => push('Ξ', 'Ξ', 'Ξ', 'Ξ'),
+        'O' => push('Ο', 'Ό', 'Ο', 'Ο'),
+        'P' => push('Π', 'Π', 'Π', 'Π'),
+        'R' => push('Ρ', 'Ρ', 'Ρ', 'Ρ'),
+        'S' => push('Σ', 'Σ', 'Σ', 'Σ'),
+        'T' => push('Τ', 'Τ', 'Τ', 'Τ'),
+        'Y' => push('Υ', 'Ύ', 'Υ', 'Υ'),
+        'F' => push('Φ', 'Φ', 'Φ', 'Φ'),
+        'X' => push('Χ', 'Χ', 'Χ', 'Χ'),
+        'C' => push('Ψ', 'Ψ', 'Ψ', 'Ψ'),
+        'V' => push('Ω', 'Ώ', 'Ω', 'Ω'),
+        'Q' => push(':', ':', ':', ':'),
+
+        _ => push(c, c, c, c),
+    }
+}
+
+/// Maps the dead keys for polytonic Greek, which are typed *before*
+/// the letter they modify (as with `;` and `:` in `push_gr_char`), to
+/// the combining marks they add. They are mostly borrowed from Beta
+/// Code.
+fn polytonic_dead_key(key: char) -> Option<char> {
+    match key {
+        ';' => Some('\u{301}'),
+        ':' => Some('\u{308}'),
+        _ => beta_code_mark(key),
+    }
+}
+
+fn push_grc_char(c: char, s: &mut String) {
+    // Letters are typed as in modern Greek.
+    let mut letter = String::new();
+    push_gr_char(c, &mut letter);
+    let mut letter = match letter.chars().next() {
+        Some(base) if letter.len() == base.len_utf8() && is_greek_letter(base) => base,
+        _ => {
+            s.push_str(&letter);
+            return;
+        }
+    };
+
+    // Find the dead keys that were typed just before this letter (they
+    // are all ASCII) and put on the marks that make sense on it. Any
+    // other dead keys are left as they were typed.
+    let dead_keys: Vec<char> = s
+        .chars()
+        .rev()
+        .take_while(|&k| polytonic_dead_key(k).is_some())
+        .collect();
+    s.truncate(s.len() - dead_keys.len());
+    for &key in dead_keys.iter().rev() {
+        match add_mark(letter, polytonic_dead_key(key).unwrap()) {
+            Some(marked) => letter = marked,
+            None => s.push(key),
+        }
+    }
+    s.push(letter);
+}
+
+/// Maps the Beta Code diacritics to the combining marks they add.
+crate fn beta_code_mark(c: char) -> Option<char> {
+    match c {
+        ')' => Some('\u{313}'),  // smooth breathing
+        '(' => Some('\u{314}'),  // rough breathing
+        '/' => Some('\u{301}'),  // acute
+        '\\' => Some('\u{300}'), // grave
+        '=' => Some('\u{342}'),  // circumflex
+        '+' => Some('\u{308}'),  // diaeresis
+        '|' => Some('\u{345}'),  // iota subscript
+        _ => None,
+    }
+}
+
+fn beta_code_letter(c: char) -> Option<char> {
+    let letter = match c.to_ascii_lowercase() {
+        'a' => 'α',
+        'b' => 'β',
+        'g' => 'γ',
+        'd' => 'δ',
+        'e' => 'ε',
+        'z' => 'ζ',
+        'h' => 'η',
+        'q' => 'θ',
+        'i' => 'ι',
+        'k' => 'κ',
+        'l' => 'λ',
+        'm' => 'μ',
+        'n' => 'ν',
+        'c' => 'ξ',
+        'o' => 'ο',
+        'p' => 'π',
+        'r' => 'ρ',
+        's' => 'ς',
+        't' => 'τ',
+        'u' => 'υ',
+        'f' => 'φ',
+        'x' => 'χ',
+        'y' => 'ψ',
+        'w' => 'ω',
+        _ => return None,
+    };
+    Some(letter)
+}
+
+fn push_beta_code_char(c: char, s: &mut String) {
+    if let Some(mark) = beta_code_mark(c) {
+        // Marks follow the letter they go on...
+        if let Some(marked) = s.chars().last().and_then(|last| add_mark(last, mark)) {
+            s.pop();
+            s.push(marked);
+        } else {
+            // ...except on capitals, where they come between the `*`
+            // and the letter, and so wait for the letter.
+            s.push(c);
+        }
+        return;
+    }
+
+    let letter = match beta_code_letter(c) {
+        Some(letter) => letter,
+        None => {
+            s.push(if c == ':' { '·' } else { c });
+            return;
+        }
+    };
+
+    let pending: Vec<char> = s
+        .chars()
+        .rev()
+        .take_while(|&k| beta_code_mark(k).is_some())
+        .collect();
+    if !s[..s.len() - pending.len()].ends_with('*') {
+        push_greek_letter(letter, s);
+        return;
+    }
+
+    s.truncate(s.len() - pending.len() - 1);
+    let mut capital = to_uppercase(letter);
+    let mut unused = String::new();
+    for &key in pending.iter().rev() {
+        match add_mark(capital, beta_code_mark(key).unwrap()) {
+            Some(marked) => capital = marked,
+            None => unused.push(key),
+        }
+    }
+    push_greek_letter(capital, s);
+    s.push_str(&unused);
+}
+
+fn push_greeklish_char(c: char, s: &mut String) {
+    // The second letter of a digraph replaces the first.
+    if let Some(previous) = s.chars().last() {
+        let digraph = match (
+            previous.to_lowercase().next().unwrap(),
+            c.to_ascii_lowercase(),
+        ) {
+            ('τ', 'h') => Some('θ'),
+            ('c', 'h') => Some('χ'),
+            ('π', 's') => Some('ψ'),
+            ('κ', 's') => Some('ξ'),
+            _ => None,
+        };
+        if let Some(letter) = digraph {
+            s.pop();
+            s.push(if previous.is_uppercase() {
+                to_uppercase(letter)
+            } else {
+                letter
+            });
+            return;
+        }
+    }
+
+    let letter = match c.to_ascii_lowercase() {
+        'a' => 'α',
+        'b' | 'v' => 'β',
+        'g' => 'γ',
+        'd' => 'δ',
+        'e' => 'ε',
+        'z' => 'ζ',
+        'h' => 'η',
+        'i' => 'ι',
+        'k' => 'κ',
+        'l' => 'λ',
+        'm' => 'μ',
+        'n' => 'ν',
+        'o' => 'ο',
+        'p' => 'π',
+        'r' => 'ρ',
+        's' => 'ς',
+        't' => 'τ',
+        'u' | 'y' => 'υ',
+        'f' => 'φ',
+        'x' => 'χ',
+        'w' => 'ω',
+        _ => {
+            s.push(if c == '?' { ';' } else { c });
+            return;
+        }
+    };
+    let mut letter = if c.is_ascii_uppercase() {
+        to_uppercase(letter)
+    } else {
+        letter
+    };
+
+    // An apostrophe before a vowel is an accent.
+    if s.ends_with('\'') {
+        if let Some(accented) = add_mark(letter, '\u{301}') {
+            s.pop();
+            letter = accented;
+        }
+    }
+    push_greek_letter(letter, s);
+}
+
+fn push_ru_char(c: char, s: &mut String) {
+    // The second letter of a digraph replaces the first.
+    if let Some(previous) = s.chars().last() {
+        let digraph = match (
+            previous.to_lowercase().next().unwrap(),
+            c.to_ascii_lowercase(),
+        ) {
+            ('з', 'h') => Some('ж'),
+            ('к', 'h') => Some('х'),
+            ('с', 'h') => Some('ш'),
+            ('ц', 'h') => Some('ч'),
+            ('е', 'h') => Some('э'),
+            ('ы', 'o') => Some('ё'),
+            ('ы', 'u') => Some('ю'),
+            ('ы', 'a') => Some('я'),
+            _ => None,
+        };
+        if let Some(mut letter) = digraph {
+            let mut previous = s.pop().unwrap();
+
+            // `shch` is `щ`, not `шч`.
+            if letter == 'ч' && s.ends_with(|c: char| c == 'ш' || c == 'Ш') {
+                previous = s.pop().unwrap();
+                letter = 'щ';
+            }
+
+            s.push(if previous.is_uppercase() {
+                to_uppercase(letter)
+            } else {
+                letter
+            });
+            return;
+        }
+    }
+
+    let letter = match c.to_ascii_lowercase() {
+        'a' => 'а',
+        'b' => 'б',
+        'v' => 'в',
+        'g' => 'г',
+        'd' => 'д',
+        'e' => 'е',
+        'z' => 'з',
+        'i' => 'и',
+        'j' => 'й',
+        'k' => 'к',
+        'l' => 'л',
+        'm' => 'м',
+        'n' => 'н',
+        'o' => 'о',
+        'p' => 'п',
+        'r' => 'р',
+        's' => 'с',
+        't' => 'т',
+        'u' => 'у',
+        'f' => 'ф',
+        'h' | 'x' => 'х',
+        'c' => 'ц',
+        'y' => 'ы',
+        '\'' => 'ь',
+        '"' => 'ъ',
+        _ => {
+            s.push(c);
+            return;
+        }
+    };
+    s.push(if c.is_ascii_uppercase() {
+        to_uppercase(letter)
+    } else {
+        letter
+    });
+}
+
+/// Pushes a Greek letter typed with a scheme that has only one key for
+/// sigma. We push `ς`, presuming that this is the end of the word,
+/// and change it to `σ` if another letter follows.
+fn push_greek_letter(letter: char, s: &mut String) {
+    if s.ends_with('ς') {
+        s.pop();
+        s.push('σ');
+    }
+    s.push(letter);
+}
+
+/// Puts the combining `mark` on `letter`, if Unicode has a precomposed
+/// character for the result. That rules out marks that make no sense
+/// on the letter (like a breathing on a consonant), or that it already
+/// has one of.
+fn add_mark(letter: char, mark: char) -> Option<char> {
+    // NFC only composes marks in the order that Unicode decomposes
+    // them: diaeresis, breathing, accent, iota subscript.
+    let mark_order = |m: char| match m {
+        '\u{308}' => 0,
+        '\u{313}' | '\u{314}' => 1,
+        '\u{345}' => 3,
+        _ => 2,
+    };
+    let mut chars: Vec<char> = letter.to_string().nfd().chain(Some(mark)).collect();
+    chars[1..].sort_by_key(|&m| mark_order(m));
+
+    let composed: String = chars.into_iter().collect::<String>().nfc().collect();
+    let mut composed = composed.chars();
+    match (composed.next(), composed.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+fn to_uppercase(c: char) -> char {
+    c.to_uppercase().next().unwrap()
+}
+
+fn is_greek_letter(c: char) -> bool {
+    match c {
+        'α'..='ω' | 'Α'..='Ω' => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn push_giasou() {
+    let string = &mut String::new();
+    for c in "g;iasoy".chars() {
+        Language::Greek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "γίασου");
+}
+
+#[test]
+fn push_throizw() {
+    let string = &mut String::new();
+    for c in "uro:;izv".chars() {
+        Language::Greek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "θροΐζω");
+}
+
+#[test]
+fn push_throizw2() {
+    let string = &mut String::new();
+    for c in "uro;:izv".chars() {
+        Language::Greek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "θροΐζω");
+}
+
+#[test]
+fn push_throizw3() {
+    let string = &mut String::new();
+    for c in "uro:;azv".chars() {
+        Language::Greek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "θρο:άζω");
+}
+
+#[test]
+fn transliterate_giasou() {
+    assert_eq!(Language::Greek.transliterate("g;iasoy"), "γίασου");
+    assert_eq!(
+        Language::Greek.transliterate("ftervt;ow"),
+        "φτερωτός"
+    );
+}
+
+#[test]
+fn push_logos() {
+    let string = &mut String::new();
+    for c in "(o l/ogow".chars() {
+        Language::AncientGreek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "ὁ λόγος");
+}
+
+#[test]
+fn push_anthropos() {
+    let string = &mut String::new();
+    for c in ")/anurvpow".chars() {
+        Language::AncientGreek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "ἄνθρωπος");
+}
+
+#[test]
+fn push_anthropos2() {
+    let string = &mut String::new();
+    for c in "/)anurvpow".chars() {
+        Language::AncientGreek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "ἄνθρωπος");
+}
+
+#[test]
+fn push_rhodos() {
+    let string = &mut String::new();
+    for c in "(R/odow".chars() {
+        Language::AncientGreek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "Ῥόδος");
+}
+
+#[test]
+fn push_polytonic_unused_dead_keys() {
+    // Consonants take no breathing, and `ε` takes no circumflex, so
+    // those dead keys are left alone.
+    let string = &mut String::new();
+    for c in "(k=e/".chars() {
+        Language::AncientGreek.push_char(c, string);
+    }
+
+    assert_eq!(&string[..], "(κ=ε/");
+}
+
+#[test]
+fn transliterate_en_arxh() {
+    assert_eq!(
+        Language::AncientGreek.transliterate(")en )arx=|h )=hn (o l/ogow"),
+        "ἐν ἀρχῇ ἦν ὁ λόγος"
+    );
+    assert_eq!(Language::AncientGreek.transliterate("pro:/izv"), "προΐζω");
+}
+
+#[test]
+fn beta_code_en_arxh() {
+    assert_eq!(
+        Language::AncientGreek.transliterate_with(
+            TransliterationScheme::BetaCode,
+            "e)n a)rxh=| h)=n o( lo/gos"
+        ),
+        "ἐν ἀρχῇ ἦν ὁ λόγος"
+    );
+}
+
+#[test]
+fn beta_code_capitals() {
+    let beta_code =
+        |text| Language::AncientGreek.transliterate_with(TransliterationScheme::BetaCode, text);
+    assert_eq!(beta_code("*)aqh=nai"), "Ἀθῆναι");
+    assert_eq!(beta_code("*(ro/dos"), "Ῥόδος");
+    assert_eq!(beta_code("*sw/zw"), "Σώζω");
+}
+
+#[test]
+fn beta_code_modern_greek() {
+    assert_eq!(
+        Language::Greek.transliterate_with(TransliterationScheme::BetaCode, "qroi+/zw"),
+        "θροΐζω"
+    );
+}
+
+#[test]
+fn greeklish_digraphs() {
+    let greeklish =
+        |text| Language::Greek.transliterate_with(TransliterationScheme::Greeklish, text);
+    assert_eq!(greeklish("thalassa"), "θαλασσα");
+    assert_eq!(greeklish("psychi"), "ψυχι");
+    assert_eq!(greeklish("ksenos"), "ξενος");
+    assert_eq!(greeklish("Thessaloniki"), "Θεσσαλονικι");
+    assert_eq!(greeklish("xronos"), "χρονος");
+}
+
+#[test]
+fn greeklish_accents() {
+    let greeklish =
+        |text| Language::Greek.transliterate_with(TransliterationScheme::Greeklish, text);
+    assert_eq!(greeklish("ner'o"), "νερό");
+    assert_eq!(greeklish("kal'a sas"), "καλά σας");
+}
+
+#[test]
+fn russian_digraphs() {
+    assert_eq!(Language::Russian.transliterate("zhurnal"), "журнал");
+    assert_eq!(Language::Russian.transliterate("khorosho"), "хорошо");
+    assert_eq!(Language::Russian.transliterate("shchi"), "щи");
+    assert_eq!(Language::Russian.transliterate("Chekhov"), "Чехов");
+    assert_eq!(Language::Russian.transliterate("yabloko"), "яблоко");
+    assert_eq!(Language::Russian.transliterate("ehto"), "это");
+}
+
+#[test]
+fn russian_signs() {
+    assert_eq!(Language::Russian.transliterate("mat'"), "мать");
+    assert_eq!(
+        Language::Russian.transliterate("ob\"yavlenie"),
+        "объявление"
+    );
+}
+
+#[test]
+fn russian_ignores_greek_schemes() {
+    assert_eq!(
+        Language::Russian.transliterate_with(TransliterationScheme::BetaCode, "zhurnal"),
+        "журнал"
+    );
+}
+
+#[test]
+fn table_groups() {
+    let table: HashMap<String, String> = [
+        ("s", "с"),
+        ("h", "х"),
+        ("c", "ц"),
+        ("i", "и"),
+        ("z", "з"),
+        ("a", "а"),
+        ("sh", "ш"),
+        ("ch", "ч"),
+        ("zh", "ж"),
+        ("shch", "щ"),
+    ]
+    .iter()
+    .map(|&(latin, native)| (latin.to_string(), native.to_string()))
+    .collect();
+    let table = TransliterationTable::new(&table);
+    assert_eq!(table.transliterate("shchi"), "щи");
+    assert_eq!(table.transliterate("Zhizha"), "Жижа");
+    assert_eq!(table.transliterate("chas"), "час");
+    assert_eq!(table.transliterate("1 chas!"), "1 час!");
+}
+
+#[test]
+fn native_input_passes_through() {
+    let mathema = |language: Language, text| {
+        language.transliterate_with(TransliterationScheme::Mathema, text)
+    };
+    assert_eq!(mathema(Language::Greek, "τι κάνεις;"), "τι κάνεις;");
+    assert_eq!(
+        Language::AncientGreek.transliterate_with(TransliterationScheme::BetaCode, "τί ἐστιν;"),
+        "τί ἐστιν;"
+    );
+    assert_eq!(mathema(Language::Russian, "что?"), "что?");
+}
+
+#[test]
+fn escape_literal_keys() {
+    let greek = |text| Language::Greek.transliterate_with(TransliterationScheme::Mathema, text);
+    assert_eq!(greek("`;a"), ";α");
+    assert_eq!(greek("to `u"), "το u");
+    assert_eq!(greek("``"), "`");
+    assert_eq!(
+        Language::Greek.transliterate_with(TransliterationScheme::Greeklish, "ti`?"),
+        "τι?"
+    );
+
+    // Nothing to escape in English, and card files have no escapes.
+    assert_eq!(
+        Language::English.transliterate_with(TransliterationScheme::Mathema, "`ls`"),
+        "`ls`"
+    );
+    assert_eq!(Language::English.transliterate("`ls`"), "`ls`");
+}
+
+#[test]
+fn transliterator_backspace() {
+    let mut transliterator = Transliterator::new(Language::Greek, TransliterationScheme::Mathema);
+    let s = &mut String::new();
+    transliterator.push('τ', s);
+    transliterator.push(';', s);
+    assert_eq!(&s[..], "τ;");
+
+    // Once the native input is gone, we transliterate again.
+    transliterator.pop(s);
+    transliterator.pop(s);
+    for c in "u;a".chars() {
+        transliterator.push(c, s);
+    }
+    assert_eq!(&s[..], "θά");
+}
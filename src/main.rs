@@ -27,18 +27,26 @@ macro throw($t: expr) {
 
 mod add;
 mod cards;
+mod config;
 mod db;
 mod dump;
 mod errors;
+mod export;
+mod fsck;
 mod git;
+mod history;
+mod import;
 mod language;
 mod line_parser;
+mod merge_db;
 mod new;
 mod prelude;
 mod quiz;
 mod selection;
 mod serve;
 mod status;
+mod storage;
+mod sync;
 mod test;
 mod uuid_ext;
 
@@ -80,6 +88,18 @@ enum MathemaCommand {
             default_value = "10"
         )]
         duration: i64,
+
+        #[structopt(
+            long = "strict",
+            help = "count answers with the wrong accents as incorrect"
+        )]
+        strict: bool,
+
+        #[structopt(
+            long = "romanize",
+            help = "also show Greek in Latin letters"
+        )]
+        romanize: bool,
     },
 
     #[structopt(name = "dump", about = "dump info about cards")]
@@ -95,21 +115,112 @@ enum MathemaCommand {
         expired: bool,
     },
 
+    #[structopt(name = "history", about = "list every answer you have given")]
+    History {
+        #[structopt(long = "csv", help = "print as CSV, for analysis in other tools")]
+        csv: bool,
+    },
+
     #[structopt(name = "new", about = "create a new deck of cards")]
     New {
         #[structopt(help = "where to create your cards")]
         directory: String,
+
+        #[structopt(
+            long = "no-git",
+            help = "keep the deck in a plain directory, without git"
+        )]
+        no_git: bool,
     },
 
     #[structopt(name = "status", about = "check on the status of your cards")]
     Status,
 
+    #[structopt(
+        name = "fsck",
+        about = "check the database against your card files"
+    )]
+    Fsck {
+        #[structopt(long = "repair", help = "fix the problems found, and commit")]
+        repair: bool,
+    },
+
+    #[structopt(
+        name = "sync",
+        about = "pull from, merge with and push to the `mathema` remote"
+    )]
+    Sync,
+
     #[structopt(name = "add", about = "add new cards from file")]
     Add {
         #[structopt(help = "new card file")]
         file: String,
     },
 
+    #[structopt(name = "export", about = "export your cards for use in other programs")]
+    Export {
+        #[structopt(
+            long = "format",
+            help = "export format (anki-tsv, json, yaml, html or markdown)"
+        )]
+        format: export::ExportFormat,
+
+        #[structopt(
+            short = "o",
+            long = "output",
+            help = "file to write to (defaults to stdout); a directory for html"
+        )]
+        output: Option<String>,
+
+        #[structopt(
+            long = "flashcards",
+            help = "add printable two-sided flashcards (html only)"
+        )]
+        flashcards: bool,
+
+        #[structopt(
+            long = "by-tag",
+            help = "group tables by tag rather than by file (markdown only)"
+        )]
+        by_tag: bool,
+
+        #[structopt(
+            long = "intervals",
+            help = "show how well each card is known (markdown only)"
+        )]
+        intervals: bool,
+    },
+
+    #[structopt(name = "import", about = "create a new deck from an export")]
+    Import {
+        #[structopt(help = "file written by `mathema export`")]
+        file: String,
+
+        #[structopt(help = "where to create your cards")]
+        directory: String,
+
+        #[structopt(
+            long = "format",
+            help = "format of the file (json or yaml; guessed from the extension by default)"
+        )]
+        format: Option<export::ExportFormat>,
+    },
+
+    #[structopt(
+        name = "merge-db",
+        about = "merge two versions of the database (used by git)"
+    )]
+    MergeDb {
+        #[structopt(help = "the version both sides started from")]
+        base: String,
+
+        #[structopt(help = "our version, which is replaced with the result")]
+        ours: String,
+
+        #[structopt(help = "their version")]
+        theirs: String,
+    },
+
     #[structopt(name = "serve", about = "serve information about your cards over JSON")]
     Serve {},
 }
@@ -133,18 +244,32 @@ fn main1() -> Result<(), Error> {
             language,
             mode,
             duration,
+            strict,
+            romanize,
         } => {
-            quiz::quiz(args, language, *mode, *duration)?;
+            quiz::quiz(args, language, *mode, *duration, *strict, *romanize)?;
+        }
+
+        MathemaCommand::History { csv } => {
+            history::history(args, *csv)?;
         }
 
-        MathemaCommand::New { directory } => {
-            new::new(args, directory)?;
+        MathemaCommand::New { directory, no_git } => {
+            new::new(args, directory, *no_git)?;
         }
 
         MathemaCommand::Status => {
             status::status(args)?;
         }
 
+        MathemaCommand::Fsck { repair } => {
+            fsck::fsck(args, *repair)?;
+        }
+
+        MathemaCommand::Sync => {
+            sync::sync(args)?;
+        }
+
         MathemaCommand::Add { file } => {
             add::add(args, file)?;
         }
@@ -153,6 +278,33 @@ fn main1() -> Result<(), Error> {
             dump::dump(args, filter, *expired)?;
         }
 
+        MathemaCommand::Export {
+            format,
+            output,
+            flashcards,
+            by_tag,
+            intervals,
+        } => {
+            let export_options = export::ExportOptions {
+                flashcards: *flashcards,
+                by_tag: *by_tag,
+                intervals: *intervals,
+            };
+            export::export(args, *format, output, &export_options)?;
+        }
+
+        MathemaCommand::Import {
+            file,
+            directory,
+            format,
+        } => {
+            import::import(args, file, directory, *format)?;
+        }
+
+        MathemaCommand::MergeDb { base, ours, theirs } => {
+            merge_db::merge_db(args, base, ours, theirs)?;
+        }
+
         MathemaCommand::Serve {} => {
             serve::serve(args)?;
         }
@@ -0,0 +1,46 @@
+//! Merge-db subcommand: the git merge driver for the database, which
+//! `mathema new` installs in `.gitattributes`. Git runs it as
+//! `mathema merge-db BASE OURS THEIRS` and expects the merged database
+//! to be written to OURS.
+
+use crate::prelude::*;
+
+crate fn merge_db(options: &MathemaOptions, base: &str, ours: &str, theirs: &str) -> Fallible<()> {
+    // Git runs merge drivers from the top of the repository, so this is
+    // where the deck configuration is.
+    let config = MathemaRepository::load_deck_config(&options.directory()?)?;
+    config.define_languages()?;
+
+    // Merging takes the union of both sides, so the base doesn't tell
+    // us anything; we only check that it can be read.
+    let _ = load(Path::new(base))?;
+    let mut database = load(Path::new(ours))?;
+    database.merge(load(Path::new(theirs))?);
+
+    if options.dry_run {
+        return Ok(());
+    }
+
+    write(Path::new(ours), &database).with_context(|_| MathemaErrorKind::AccessingFile {
+        file: ours.to_string(),
+    })?;
+
+    Ok(())
+}
+
+fn write(path: &Path, database: &Database) -> Fallible<()> {
+    let string = database.to_string_keeping_records()?;
+    AtomicFile::new(path, OverwriteBehavior::AllowOverwrite)
+        .write(|f| f.write_all(string.as_bytes()))?;
+    Ok(())
+}
+
+fn load(path: &Path) -> Fallible<Database> {
+    Ok(
+        MathemaRepository::read_from(path, |f| Database::load_from(f)).with_context(|_| {
+            MathemaErrorKind::CannotLoadDatabase {
+                database_path: path.display().to_string(),
+            }
+        })?,
+    )
+}
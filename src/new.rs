@@ -1,8 +1,9 @@
 //! New subcommand.
 
 use crate::prelude::*;
+use crate::storage::StorageKind;
 
-crate fn new(options: &MathemaOptions, new_directory: &str) -> Fallible<()> {
+crate fn new(options: &MathemaOptions, new_directory: &str, no_git: bool) -> Fallible<()> {
     let MathemaOptions {
         directory,
         force,
@@ -31,7 +32,13 @@ crate fn new(options: &MathemaOptions, new_directory: &str) -> Fallible<()> {
         });
     }
 
-    match new_atomic(new_directory) {
+    let storage_kind = if no_git {
+        StorageKind::Directory
+    } else {
+        StorageKind::Git
+    };
+
+    match new_atomic(new_directory, storage_kind) {
         Ok(()) => Ok(()),
         Err(e) => {
             let _ = fs::remove_dir_all(&new_directory); // if this fails, oh well
@@ -40,8 +47,8 @@ crate fn new(options: &MathemaOptions, new_directory: &str) -> Fallible<()> {
     }
 }
 
-fn new_atomic(directory: impl AsRef<Path>) -> Fallible<()> {
-    let _ = MathemaRepository::create_on_disk(&directory)?;
+fn new_atomic(directory: impl AsRef<Path>, storage_kind: StorageKind) -> Fallible<()> {
+    let _ = MathemaRepository::create_on_disk(&directory, storage_kind)?;
 
     Ok(())
 }
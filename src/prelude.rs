@@ -2,15 +2,19 @@
 
 crate use crate::{
     cards::{self, Card, CardLine, LineKind},
+    config::{ArticleRule, DeckConfig, UserConfig},
     db::{CardRecord, Database, QuestionKind, QuestionRecord, QuestionResult, User},
     errors::{Fallible, MathemaError, MathemaErrorKind},
     git::MathemaRepository,
-    language::Language,
+    language::{
+        InputMethod, Language, LanguageDefinition, RomanizationScheme, TransliterationScheme,
+        Transliterator,
+    },
     line_parser::LineParser,
     quiz,
     quiz::presentation::text::{TextDelegate, TextPresentation},
     quiz::presentation::{Presentation, PresentationMode, Prompt},
-    quiz::SUITABLE_QUESTIONS,
+    quiz::{AnswerSyntax, GradedResponse, Normalization, ResponseMatch},
     selection,
     status::Status,
     throw,
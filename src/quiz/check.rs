@@ -0,0 +1,539 @@
+//! Comparing the user's responses with the responses we expect.
+
+use crate::prelude::*;
+use std::cmp;
+use unicode_normalization::{char::is_combining_mark, UnicodeNormalization};
+
+lazy_static! {
+    static ref PARENTHETICALS: Regex = Regex::new(r"\(.*\)").unwrap();
+}
+
+/// How closely a user's response matched an expected response. The
+/// variants are ordered from best to worst, so the best of several
+/// matches is the minimum.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+crate enum ResponseMatch {
+    /// The response is right.
+    Exact,
+
+    /// The response is right apart from its accents and/or its
+    /// sigmas (`σ` where `ς` belongs, or vice versa).
+    Almost { accents: bool, sigma: bool },
+
+    /// The response is wrong, but only by a typo or two: it is
+    /// `distance` edits away from the expected response.
+    NearMiss { distance: usize },
+
+    /// The response is wrong.
+    Wrong,
+}
+
+impl ResponseMatch {
+    /// Does this match count as having given the expected response? In
+    /// strict mode, only an exact match does.
+    crate fn is_correct(self, strict: bool) -> bool {
+        match self {
+            ResponseMatch::Exact => true,
+            ResponseMatch::Almost { .. } => !strict,
+            ResponseMatch::NearMiss { .. } | ResponseMatch::Wrong => false,
+        }
+    }
+
+    /// Was the response close enough that "almost" is a fairer result
+    /// than "yes" or "no"?
+    crate fn suggests_almost(self) -> bool {
+        match self {
+            ResponseMatch::Almost { .. } | ResponseMatch::NearMiss { .. } => true,
+            ResponseMatch::Exact | ResponseMatch::Wrong => false,
+        }
+    }
+
+    /// A short description of what was off, for showing to the user.
+    crate fn description(self) -> Option<&'static str> {
+        match self {
+            ResponseMatch::Exact | ResponseMatch::Wrong => None,
+            ResponseMatch::Almost {
+                accents: true,
+                sigma: true,
+            } => Some("accents and sigma differ"),
+            ResponseMatch::Almost { accents: true, .. } => Some("accents differ"),
+            ResponseMatch::Almost { .. } => Some("sigma differs"),
+            ResponseMatch::NearMiss { .. } => Some("close"),
+        }
+    }
+}
+
+/// A response given by the user, and how it compared with the
+/// responses we expected.
+#[derive(Clone, Debug)]
+crate struct GradedResponse {
+    crate text: String,
+    crate response_match: ResponseMatch,
+
+    /// Whether the response counted as giving the expected response.
+    crate correct: bool,
+
+    /// The expected response that it matched best, unless it was wrong.
+    crate expected: Option<String>,
+}
+
+impl GradedResponse {
+    /// Explains what was off about the response, if it was close.
+    crate fn feedback(&self) -> Option<String> {
+        let expected = self.expected.as_ref()?;
+        match self.response_match {
+            ResponseMatch::Exact | ResponseMatch::Wrong => None,
+            ResponseMatch::Almost { .. } => Some(format!(
+                "{}: `{}`",
+                self.response_match.description()?,
+                expected
+            )),
+            ResponseMatch::NearMiss { .. } => Some(format!("did you mean `{}`?", expected)),
+        }
+    }
+}
+
+/// Grades the responses to one question as the user gives them. Each
+/// expected response can only be given once: once a response has
+/// matched it, it no longer counts as missing, and later responses
+/// can't match it again.
+crate struct ResponseGrader<'e> {
+    syntax: AnswerSyntax,
+    strict: bool,
+    missing: Vec<&'e str>,
+    responses: Vec<GradedResponse>,
+}
+
+impl<'e> ResponseGrader<'e> {
+    crate fn new(
+        expected_responses: impl IntoIterator<Item = &'e str>,
+        syntax: AnswerSyntax,
+        strict: bool,
+    ) -> Self {
+        ResponseGrader {
+            syntax,
+            strict,
+            missing: expected_responses.into_iter().collect(),
+            responses: vec![],
+        }
+    }
+
+    crate fn grade(&mut self, user_response: String) -> &GradedResponse {
+        let (syntax, strict) = (self.syntax, self.strict);
+        let mut best: Option<(ResponseMatch, &'e str)> = None;
+        self.missing.retain(|&r| {
+            let response_match = check_user_response(r, &user_response, syntax);
+            if best.map(|(m, _)| response_match < m).unwrap_or(true) {
+                best = Some((response_match, r));
+            }
+            !response_match.is_correct(strict)
+        });
+
+        let (response_match, expected) = best.unwrap_or((ResponseMatch::Wrong, ""));
+        self.responses.push(GradedResponse {
+            text: user_response,
+            response_match,
+            correct: response_match.is_correct(strict),
+            expected: match response_match {
+                ResponseMatch::Wrong => None,
+                _ => Some(expected.to_string()),
+            },
+        });
+        self.responses.last().unwrap()
+    }
+
+    /// Every response graded so far, in the order they were given.
+    crate fn responses(&self) -> &[GradedResponse] {
+        &self.responses
+    }
+
+    /// The expected responses that the user has not given yet.
+    crate fn missing(&self) -> &[&'e str] {
+        &self.missing
+    }
+
+    /// The result the user deserves given the responses so far: "yes"
+    /// if they gave every expected response, "almost" if some response
+    /// was a near miss (like a missing accent or a typo), and no
+    /// suggestion if they simply got things wrong.
+    crate fn suggested_result(&self) -> Option<QuestionResult> {
+        if self
+            .responses
+            .iter()
+            .any(|r| r.response_match.suggests_almost())
+        {
+            Some(QuestionResult::Almost)
+        } else if self.missing.is_empty() {
+            Some(QuestionResult::Yes)
+        } else {
+            None
+        }
+    }
+}
+
+/// Language-specific leniency in how answers are checked; see
+/// `DeckConfig::answer_syntax`.
+#[derive(Copy, Clone, Debug, Default)]
+crate struct AnswerSyntax {
+    /// Articles that the user may leave off the front of an answer.
+    crate optional_articles: &'static [&'static str],
+
+    /// How to normalize responses before comparing them.
+    crate normalization: &'static [Normalization],
+}
+
+/// A rule for normalizing responses before comparing them, so that
+/// differences that don't matter for a language don't count at all.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+crate enum Normalization {
+    /// Ignore stress marks, written as acute (or sometimes grave)
+    /// accents, which textbooks add to show pronunciation (e.g.,
+    /// `молоко́`). Other marks, like the diaeresis on `ё`, still count.
+    IgnoreStress,
+
+    /// Ignore macrons and breves, which textbooks add to show vowel
+    /// length (e.g., `rēx`).
+    IgnoreVowelLength,
+
+    /// Ignore upper and lower case.
+    IgnoreCase,
+}
+
+impl Normalization {
+    fn apply(self, text: &str) -> String {
+        match self {
+            Normalization::IgnoreStress => strip_marks(text, &['\u{301}', '\u{300}']),
+            Normalization::IgnoreVowelLength => strip_marks(text, &['\u{304}', '\u{306}']),
+            Normalization::IgnoreCase => text.to_lowercase(),
+        }
+    }
+}
+
+/// Compares the user's response with an expected response, which may
+/// use a little syntax of its own:
+///
+/// - `(...)` is an explanation, never part of the answer;
+/// - `a, b` means that `a`, `b` and `a, b` are all acceptable;
+/// - `a|b` within a word means that either `a` or `b` is acceptable there;
+/// - `[...]` is optional.
+crate fn check_user_response(
+    expected_response: &str,
+    user_response: &str,
+    syntax: AnswerSyntax,
+) -> ResponseMatch {
+    let mut user_response = normalize(user_response.trim());
+    let mut expected_response = normalize(&PARENTHETICALS.replace_all(expected_response, ""));
+    for rule in syntax.normalization {
+        user_response = rule.apply(&user_response);
+        expected_response = rule.apply(&expected_response);
+    }
+    // Expand `[...]` before splitting at commas, which may be inside
+    // the brackets.
+    let alternatives = expand_optional(&expected_response)
+        .into_iter()
+        .flat_map(|text| {
+            let mut alternatives: Vec<String> = text.split(",").map(str::to_string).collect();
+            alternatives.push(text);
+            alternatives
+        });
+    alternatives
+        .flat_map(|r| expand(&r, syntax))
+        .map(|r| compare(&r, &user_response))
+        .fold(ResponseMatch::Wrong, cmp::min)
+}
+
+/// Expands one alternative of an expected response (with its `[...]`
+/// segments already expanded) into all the strings that it accepts.
+fn expand(alternative: &str, syntax: AnswerSyntax) -> Vec<String> {
+    // Expand `a|b` word by word.
+    let mut words_so_far = vec![String::new()];
+    for word in alternative.split_whitespace() {
+        words_so_far = words_so_far
+            .iter()
+            .cartesian_product(word.split("|"))
+            .map(|(prefix, choice)| {
+                if prefix.is_empty() {
+                    choice.to_string()
+                } else {
+                    format!("{} {}", prefix, choice)
+                }
+            })
+            .collect();
+    }
+
+    let mut results = vec![];
+    for result in words_so_far {
+        let mut words = result.splitn(2, ' ');
+        if let (Some(first), Some(rest)) = (words.next(), words.next()) {
+            if syntax.optional_articles.iter().any(|&a| a == first) {
+                results.push(rest.to_string());
+            }
+        }
+        results.push(result);
+    }
+    results
+}
+
+/// Expands the `[...]` segments of `text`: each is either kept (without
+/// its brackets) or dropped.
+fn expand_optional(text: &str) -> Vec<String> {
+    let start = match text.find('[') {
+        Some(start) => start,
+        None => return vec![text.to_string()],
+    };
+    let end = match text[start..].find(']') {
+        Some(end) => start + end,
+        None => return vec![text.to_string()],
+    };
+
+    let (prefix, optional, suffix) = (&text[..start], &text[start + 1..end], &text[end + 1..]);
+    expand_optional(suffix)
+        .into_iter()
+        .flat_map(|suffix| {
+            vec![
+                format!("{}{}{}", prefix, optional, suffix),
+                format!("{}{}", prefix, suffix),
+            ]
+        })
+        .collect()
+}
+
+fn compare(expected: &str, user: &str) -> ResponseMatch {
+    if expected == user {
+        return ResponseMatch::Exact;
+    }
+
+    let (expected_bare, user_bare) = (strip_accents(expected), strip_accents(user));
+    if expected_bare == user_bare {
+        ResponseMatch::Almost {
+            accents: true,
+            sigma: false,
+        }
+    } else if fold_sigma(expected) == fold_sigma(user) {
+        ResponseMatch::Almost {
+            accents: false,
+            sigma: true,
+        }
+    } else if fold_sigma(&expected_bare) == fold_sigma(&user_bare) {
+        ResponseMatch::Almost {
+            accents: true,
+            sigma: true,
+        }
+    } else {
+        // Accents and sigmas were dealt with above, so don't let them
+        // count towards the typos.
+        let (expected_bare, user_bare) = (fold_sigma(&expected_bare), fold_sigma(&user_bare));
+        let distance = edit_distance(&expected_bare, &user_bare);
+        if distance <= allowed_typos(&expected_bare) {
+            ResponseMatch::NearMiss { distance }
+        } else {
+            ResponseMatch::Wrong
+        }
+    }
+}
+
+/// How many typos we forgive in a response: one for every four
+/// letters, so short words must be spelled exactly.
+fn allowed_typos(expected: &str) -> usize {
+    expected.chars().count() / 4
+}
+
+/// The Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, &cb) in b.iter().enumerate() {
+            let substitution = previous[j] + if ca == cb { 0 } else { 1 };
+            let deletion = previous[j + 1] + 1;
+            let insertion = current[j] + 1;
+            current.push(substitution.min(deletion).min(insertion));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Composes characters, so that text typed as a letter plus a
+/// combining accent compares equal to text using precomposed letters.
+fn normalize(text: &str) -> String {
+    text.nfc().collect()
+}
+
+fn strip_accents(text: &str) -> String {
+    text.nfd()
+        .filter(|&c| !is_combining_mark(c))
+        .nfc()
+        .collect()
+}
+
+fn strip_marks(text: &str, marks: &[char]) -> String {
+    text.nfd().filter(|c| !marks.contains(c)).nfc().collect()
+}
+
+fn fold_sigma(text: &str) -> String {
+    text.replace('ς', "σ")
+}
+
+#[cfg(test)]
+fn check(expected_response: &str, user_response: &str) -> ResponseMatch {
+    check_user_response(expected_response, user_response, AnswerSyntax::default())
+}
+
+#[test]
+fn check_user_response1() {
+    let is_correct = |e, u| check(e, u).is_correct(true);
+    assert!(is_correct("a, b (c)", "a"));
+    assert!(is_correct("a, b (c)", "b"));
+    assert!(is_correct("a, b (c)", "b "));
+    assert!(is_correct("a, b (c)", "a, b"));
+    assert!(is_correct("a, b (c)", " a, b"));
+    assert!(!is_correct("a, b (c)", "c"));
+}
+
+#[test]
+fn check_user_response_normalization() {
+    // `ά` as alpha followed by a combining acute accent
+    assert_eq!(check("μάθημα", "μα\u{301}θημα"), ResponseMatch::Exact);
+}
+
+#[test]
+fn check_user_response_stress() {
+    let russian = AnswerSyntax {
+        normalization: &[Normalization::IgnoreStress],
+        ..AnswerSyntax::default()
+    };
+    assert_eq!(
+        check_user_response("молоко\u{301}", "молоко", russian),
+        ResponseMatch::Exact
+    );
+    assert_eq!(
+        check_user_response("молоко", "моло\u{301}ко", russian),
+        ResponseMatch::Exact
+    );
+
+    // The diaeresis on `ё` is part of the spelling.
+    assert!(check_user_response("ёлка", "елка", russian).suggests_almost());
+
+    // Elsewhere, a stress mark is just another accent.
+    assert!(check("молоко\u{301}", "молоко").suggests_almost());
+}
+
+#[test]
+fn check_user_response_vowel_length_and_case() {
+    let latin = AnswerSyntax {
+        normalization: &[Normalization::IgnoreVowelLength, Normalization::IgnoreCase],
+        ..AnswerSyntax::default()
+    };
+    assert_eq!(
+        check_user_response("rēx", "Rex", latin),
+        ResponseMatch::Exact
+    );
+    assert_eq!(
+        check_user_response("Rōma", "roma", latin),
+        ResponseMatch::Exact
+    );
+    assert!(!check("Rōma", "roma").is_correct(true));
+}
+
+#[test]
+fn check_user_response_accents() {
+    assert_eq!(
+        check("το μάθημα", "το μαθημα"),
+        ResponseMatch::Almost {
+            accents: true,
+            sigma: false
+        }
+    );
+    assert_eq!(
+        check("θροΐζω", "θροιζω"),
+        ResponseMatch::Almost {
+            accents: true,
+            sigma: false
+        }
+    );
+}
+
+#[test]
+fn check_user_response_sigma() {
+    assert_eq!(
+        check("φτερωτός", "φτερωτόσ"),
+        ResponseMatch::Almost {
+            accents: false,
+            sigma: true
+        }
+    );
+    assert_eq!(
+        check("φτερωτός", "φτερωτοσ"),
+        ResponseMatch::Almost {
+            accents: true,
+            sigma: true
+        }
+    );
+    assert_eq!(check("φτερωτός", "φτερωτή"), ResponseMatch::Wrong);
+}
+
+#[test]
+fn check_user_response_typos() {
+    assert_eq!(edit_distance("μάθημα", "μάθημα"), 0);
+    assert_eq!(edit_distance("lesson", "leson"), 1);
+    assert_eq!(edit_distance("lesson", "lessno"), 2);
+
+    assert_eq!(
+        check("lesson", "leson"),
+        ResponseMatch::NearMiss { distance: 1 }
+    );
+    assert_eq!(
+        check("το μάθημα", "το μαθιμα"),
+        ResponseMatch::NearMiss { distance: 1 }
+    );
+    assert_eq!(check("yes", "yse"), ResponseMatch::Wrong);
+    assert_eq!(check("lesson", "lemon"), ResponseMatch::Wrong);
+}
+
+#[test]
+fn check_user_response_syntax() {
+    assert_eq!(check("[to] go", "go"), ResponseMatch::Exact);
+    assert_eq!(check("[to] go", "to go"), ResponseMatch::Exact);
+    assert_eq!(check("go [back] home", "go home"), ResponseMatch::Exact);
+    assert_eq!(check("ο|η φίλος", "η φίλος"), ResponseMatch::Exact);
+    assert_eq!(check("ο|η φίλος", "ο φίλος"), ResponseMatch::Exact);
+    assert_eq!(check("[ο|η] φίλος", "φίλος"), ResponseMatch::Exact);
+    assert_eq!(check("[ο|η] φίλος", "οι φίλοι"), ResponseMatch::Wrong);
+    assert_eq!(check("go [out, away]", "go out"), ResponseMatch::Exact);
+    assert_eq!(check("go [out, away]", "go"), ResponseMatch::Exact);
+}
+
+#[test]
+fn check_user_response_articles() {
+    let optional = AnswerSyntax {
+        optional_articles: Language::Greek.articles(),
+        ..AnswerSyntax::default()
+    };
+    assert_eq!(check("το μάθημα", "μάθημα"), ResponseMatch::Wrong);
+    assert_eq!(
+        check_user_response("το μάθημα", "μάθημα", optional),
+        ResponseMatch::Exact
+    );
+    assert_eq!(
+        check_user_response("το μάθημα", "το μάθημα", optional),
+        ResponseMatch::Exact
+    );
+}
+
+#[test]
+fn grade_responses() {
+    let mut grader = ResponseGrader::new(vec!["yes", "certainly"], AnswerSyntax::default(), false);
+    assert!(grader.grade("yes".to_string()).correct);
+    assert!(!grader.grade("yes".to_string()).correct);
+    assert_eq!(grader.missing(), &["certainly"]);
+    assert_eq!(grader.suggested_result(), None);
+
+    assert!(!grader.grade("certanly".to_string()).correct);
+    assert_eq!(grader.suggested_result(), Some(QuestionResult::Almost));
+
+    let mut grader = ResponseGrader::new(vec!["yes"], AnswerSyntax::default(), false);
+    grader.grade("yes".to_string());
+    assert_eq!(grader.suggested_result(), Some(QuestionResult::Yes));
+}
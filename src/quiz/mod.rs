@@ -1,23 +1,42 @@
 use crate::prelude::*;
 
+crate mod check;
 crate mod presentation;
 
-crate const SUITABLE_QUESTIONS: &[(Language, &[QuestionKind])] = &[(
-    Language::Greek,
-    &[
+crate use self::check::{
+    check_user_response, AnswerSyntax, GradedResponse, Normalization, ResponseGrader,
+    ResponseMatch,
+};
+
+/// The questions we ask to quiz someone on `language`: translating to
+/// and from English.
+crate fn suitable_questions(language: Language) -> Vec<QuestionKind> {
+    if language == Language::English {
+        return vec![];
+    }
+
+    vec![
         QuestionKind::Translate {
             from: Language::English,
-            to: Language::Greek,
+            to: language,
         },
         QuestionKind::Translate {
-            from: Language::Greek,
+            from: language,
             to: Language::English,
         },
-    ],
-)];
+    ]
+}
 
-lazy_static! {
-    static ref PARENTHETICALS: Regex = Regex::new(r"\(.*\)").unwrap();
+/// Every kind of question we might ask, for any language, without
+/// duplicates.
+crate fn all_question_kinds() -> Vec<QuestionKind> {
+    let mut question_kinds: Vec<QuestionKind> = Language::all()
+        .into_iter()
+        .flat_map(suitable_questions)
+        .collect();
+    question_kinds.sort();
+    question_kinds.dedup();
+    question_kinds
 }
 
 crate fn quiz(
@@ -25,6 +44,8 @@ crate fn quiz(
     language_str: &str,
     mode: Option<PresentationMode>,
     duration_min: i64,
+    strict: bool,
+    romanize: bool,
 ) -> Fallible<()> {
     let rng = &mut rand::thread_rng();
 
@@ -34,17 +55,17 @@ crate fn quiz(
         Some(_) => PresentationMode::Ncurses,
     });
 
-    let language = Language::from_str(language_str)?;
+    // Open the repository first, as it may define the language.
+    let repo = &mut MathemaRepository::open(options)?;
 
-    let suitable_questions = SUITABLE_QUESTIONS
-        .iter()
-        .filter_map(|(l, qks)| if *l == language { Some(*qks) } else { None })
-        .next()
-        .ok_or(MathemaErrorKind::DontKnowHowToQuiz {
+    let language = Language::from_str(language_str)?;
+    let suitable_questions = suitable_questions(language);
+    if suitable_questions.is_empty() {
+        throw!(MathemaErrorKind::DontKnowHowToQuiz {
             language: language.full_name(),
-        })?;
+        });
+    }
 
-    let repo = &mut MathemaRepository::open(options)?;
     let status = repo.load_cards()?;
     if status.warn_if_needed(options.force) {
         return Ok(());
@@ -52,6 +73,12 @@ crate fn quiz(
 
     let parentheticals = Regex::new(r"\(.*\)").unwrap();
 
+    let romanization = match repo.user_config().romanization {
+        Some(scheme) => Some(scheme),
+        None if romanize => Some(RomanizationScheme::default()),
+        None => None,
+    };
+
     Quiz {
         rng,
         repo,
@@ -61,6 +88,8 @@ crate fn quiz(
         duration_min,
         suitable_questions,
         mode,
+        strict,
+        romanization,
     }
     .run()
 }
@@ -72,8 +101,14 @@ struct Quiz<'o> {
     language: Language,
     parentheticals: Regex,
     duration_min: i64,
-    suitable_questions: &'o [QuestionKind],
+    suitable_questions: Vec<QuestionKind>,
     mode: PresentationMode,
+
+    /// If true, responses with the wrong accents are incorrect.
+    strict: bool,
+
+    /// If some, how to romanize the Greek we show.
+    romanization: Option<RomanizationScheme>,
 }
 
 impl Quiz<'_> {
@@ -82,7 +117,7 @@ impl Quiz<'_> {
         let mut start_time = Utc::now();
         let mut max_duration = Duration::minutes(self.duration_min);
 
-        let cards = selection::expired_cards(self.rng, self.repo, self.suitable_questions);
+        let cards = selection::expired_cards(self.rng, self.repo, &self.suitable_questions);
 
         let mut presentation = Presentation::with_mode(self.mode);
 
@@ -104,7 +139,7 @@ impl Quiz<'_> {
             }
 
             let card = self.repo.card(uuid);
-            let mut expected_responses: Vec<_> = card
+            let expected_responses: Vec<_> = card
                 .lines_with_kind(question_kind.response_line_kind())
                 .collect();
 
@@ -113,23 +148,25 @@ impl Quiz<'_> {
                 card,
                 question_kind,
                 num_responses: expected_responses.len(),
+                transliteration: self
+                    .repo
+                    .user_config()
+                    .transliteration(question_kind.response_language()),
+                romanization: self.romanization,
             };
 
             presentation.start_prompt(prompt)?;
 
+            let syntax = self
+                .repo
+                .config()
+                .answer_syntax(question_kind.response_language());
+
             let mut counter = 1;
             let total_responses = expected_responses.len();
-            let mut incorrect_responses = vec![];
-            let mut correct_responses = vec![];
+            let mut grader = ResponseGrader::new(expected_responses, syntax, self.strict);
             while let Some(user_response) = presentation.read_response(prompt, counter)? {
-                let len_before = expected_responses.len();
-                expected_responses.retain(|r| !check_user_response(r, &user_response));
-
-                if expected_responses.len() != len_before {
-                    correct_responses.push(user_response);
-                } else {
-                    incorrect_responses.push(user_response);
-                }
+                grader.grade(user_response);
 
                 if counter >= total_responses {
                     break;
@@ -138,15 +175,14 @@ impl Quiz<'_> {
                 counter += 1;
             }
 
-            let result = if expected_responses.is_empty() {
-                QuestionResult::Yes
-            } else {
-                presentation.read_result(
+            let result = match grader.suggested_result() {
+                Some(QuestionResult::Yes) => QuestionResult::Yes,
+                suggested => presentation.read_result(
                     prompt,
-                    &expected_responses,
-                    &correct_responses,
-                    &incorrect_responses,
-                )?
+                    grader.missing(),
+                    grader.responses(),
+                    suggested,
+                )?,
             };
 
             // If they said NO, then let's have them repeat until everything looks
@@ -160,7 +196,9 @@ impl Quiz<'_> {
                         'next_word: loop {
                             match presentation.repeat_back(prompt, response)? {
                                 Some(user_response) => {
-                                    if check_user_response(response, &user_response) {
+                                    if check_user_response(response, &user_response, syntax)
+                                        .is_correct(self.strict)
+                                    {
                                         break 'next_word;
                                     }
                                 }
@@ -172,42 +210,33 @@ impl Quiz<'_> {
                 }
             }
 
-            let record = self.repo.database_mut().card_record_mut(uuid);
-            record.push_question_record(
+            self.repo.record_answer(
+                uuid,
                 question_kind,
                 QuestionRecord {
                     date: Utc::now(),
                     result: result,
                 },
-            );
+            )?;
 
             presentation.cleanup();
         }
 
         mem::drop(presentation);
 
-        self.repo.write_database()?;
+        let message = format!(
+            "quiz: {}, {} min",
+            self.repo.answers_summary(),
+            Utc::now()
+                .signed_duration_since(original_start_time)
+                .num_minutes()
+        );
+        self.repo.write_database(&message)?;
+
+        if self.repo.user_config().sync_after_quiz && !self.options.dry_run {
+            crate::sync::report(self.repo.sync()?);
+        }
 
         Ok(())
     }
 }
-
-crate fn check_user_response(expected_response: &str, user_response: &str) -> bool {
-    let user_response = user_response.trim();
-    let expected_response = PARENTHETICALS.replace_all(expected_response, "");
-    expected_response.trim() == user_response || {
-        expected_response
-            .split(",")
-            .any(|r| r.trim() == user_response)
-    }
-}
-
-#[test]
-fn check_user_response1() {
-    assert!(check_user_response("a, b (c)", "a"));
-    assert!(check_user_response("a, b (c)", "b"));
-    assert!(check_user_response("a, b (c)", "b "));
-    assert!(check_user_response("a, b (c)", "a, b"));
-    assert!(check_user_response("a, b (c)", " a, b"));
-    assert!(!check_user_response("a, b (c)", "c"));
-}
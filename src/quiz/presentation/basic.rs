@@ -20,7 +20,7 @@ impl TextDelegate for Basic {
         let mut buffer = String::new();
         self.stdin.read_line(&mut buffer)?;
         let response_language = prompt.question_kind.response_language();
-        let response = response_language.transliterate(buffer.trim());
+        let response = response_language.transliterate_with(prompt.transliteration, buffer.trim());
         if response != buffer.trim() {
             println!("  (transliterated to `{}`)", response);
         }
@@ -31,11 +31,16 @@ impl TextDelegate for Basic {
         }
     }
 
-    fn read_result(&mut self, _prompt: Prompt<'_>) -> Fallible<Option<QuestionResult>> {
+    fn read_result(
+        &mut self,
+        _prompt: Prompt<'_>,
+        suggested: Option<QuestionResult>,
+    ) -> Fallible<Option<QuestionResult>> {
         let mut buffer = String::new();
         self.stdin.read_line(&mut buffer)?;
         let buffer = buffer.trim().to_lowercase();
         match &buffer[..] {
+            "" => Ok(suggested),
             "yes" | "y" => Ok(Some(QuestionResult::Yes)),
             "almost" | "a" => Ok(Some(QuestionResult::Almost)),
             "no" | "n" => Ok(Some(QuestionResult::No)),
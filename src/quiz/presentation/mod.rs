@@ -36,13 +36,15 @@ crate trait Presentation {
 
     /// Invoked once user has stopped supplying answers, if they did
     /// not get everything right; `missing_answers` is the list of
-    /// answers we did not see from them.
+    /// answers we did not see from them, and `answers` are the ones we
+    /// did see. If `suggested` is some, that is the result we think
+    /// they deserve.
     fn read_result(
         &mut self,
         prompt: Prompt<'_>,
         missing_answers: &[&str],
-        correct_answers: &[String],
-        incorrect_answers: &[String],
+        answers: &[GradedResponse],
+        suggested: Option<QuestionResult>,
     ) -> Fallible<QuestionResult>;
 
     /// Invoked repeatedly if user says they got it wrong.
@@ -73,6 +75,12 @@ crate struct Prompt<'p> {
     crate card: &'p Card,
     crate question_kind: QuestionKind,
     crate num_responses: usize,
+
+    /// How the user types responses.
+    crate transliteration: TransliterationScheme,
+
+    /// If some, how to romanize the Greek we show the user.
+    crate romanization: Option<RomanizationScheme>,
 }
 
 #[derive(Copy, Clone, Debug)]
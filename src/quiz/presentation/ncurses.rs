@@ -25,9 +25,11 @@ impl Ncurses {
         Ok(ch)
     }
 
+    /// Reads a line, transliterating it as the user types if
+    /// `transliterator` is some.
     fn read_line(
         &mut self,
-        mut push_char: impl FnMut(char, &mut String),
+        mut transliterator: Option<Transliterator>,
     ) -> Fallible<Option<String>> {
         let mut buffer = String::new();
         loop {
@@ -38,12 +40,20 @@ impl Ncurses {
                 break;
             } else if ch == ncurses::KEY_BACKSPACE || ch == 0x7f {
                 // For some reason, I sometimes see `0x7f` for backspace.
-                buffer.pop();
+                match &mut transliterator {
+                    Some(transliterator) => transliterator.pop(&mut buffer),
+                    None => {
+                        buffer.pop();
+                    }
+                }
                 let new_len = buffer.chars().count();
                 check_ret!(ncurses::mvprintw(self.row, new_len as i32, " "));
             } else {
                 let ch = char::from_u32(ch as u32).unwrap();
-                push_char(ch, &mut buffer);
+                match &mut transliterator {
+                    Some(transliterator) => transliterator.push(ch, &mut buffer),
+                    None => buffer.push(ch),
+                }
             }
         }
         if buffer.is_empty() {
@@ -71,11 +81,21 @@ impl TextDelegate for Ncurses {
 
     fn read_answer(&mut self, prompt: Prompt<'_>) -> Fallible<Option<String>> {
         let response_language = prompt.question_kind.response_language();
-        self.read_line(|c, b| response_language.push_char(c, b))
+        self.read_line(Some(Transliterator::new(
+            response_language,
+            prompt.transliteration,
+        )))
     }
 
-    fn read_result(&mut self, _prompt: Prompt<'_>) -> Fallible<Option<QuestionResult>> {
+    fn read_result(
+        &mut self,
+        _prompt: Prompt<'_>,
+        suggested: Option<QuestionResult>,
+    ) -> Fallible<Option<QuestionResult>> {
         let ch = self.getch()?;
+        if ch == ncurses::KEY_ENTER || ch == 0xA {
+            return Ok(suggested);
+        }
         match char::from_u32(ch as u32).unwrap() {
             'y' => Ok(Some(QuestionResult::Yes)),
             'a' => Ok(Some(QuestionResult::Almost)),
@@ -85,7 +105,7 @@ impl TextDelegate for Ncurses {
     }
 
     fn read_minutes(&mut self) -> Fallible<Option<String>> {
-        self.read_line(|c, b| b.push(c))
+        self.read_line(None)
     }
 
     fn cleanup(&mut self) {
@@ -12,7 +12,13 @@ impl<D: TextDelegate> TextPresentation<D> {
 
 crate trait TextDelegate {
     fn read_answer(&mut self, prompt: Prompt<'_>) -> Fallible<Option<String>>;
-    fn read_result(&mut self, prompt: Prompt<'_>) -> Fallible<Option<QuestionResult>>;
+    /// Reads "yes", "almost" or "no"; if the user just presses enter,
+    /// returns `suggested`.
+    fn read_result(
+        &mut self,
+        prompt: Prompt<'_>,
+        suggested: Option<QuestionResult>,
+    ) -> Fallible<Option<QuestionResult>>;
     fn read_minutes(&mut self) -> Fallible<Option<String>>;
     fn cleanup(&mut self);
     fn println(&mut self, text: &str) -> Fallible<()>;
@@ -30,8 +36,9 @@ impl<D: TextDelegate> Presentation for TextPresentation<D> {
     fn start_prompt(&mut self, prompt: Prompt<'_>) -> Fallible<()> {
         println!(self, "Please {}:", prompt.question_kind.prompt_text());
         let prompt_line_kind = prompt.question_kind.prompt_line_kind();
+        let prompt_language = prompt.question_kind.prompt_language();
         for line in prompt.card.lines_with_kind(prompt_line_kind) {
-            println!(self, "- {}", line);
+            println!(self, "- {}", romanized(prompt, prompt_language, line));
         }
         Ok(())
     }
@@ -45,33 +52,52 @@ impl<D: TextDelegate> Presentation for TextPresentation<D> {
         &mut self,
         prompt: Prompt<'_>,
         missing_answers: &[&str],
-        correct_answers: &[String],
-        incorrect_answers: &[String],
+        answers: &[GradedResponse],
+        suggested: Option<QuestionResult>,
     ) -> Fallible<QuestionResult> {
+        let (correct_answers, incorrect_answers): (Vec<_>, Vec<_>) =
+            answers.iter().partition(|a| a.correct);
+
         if !incorrect_answers.is_empty() {
             println!(self, "Incorrect answers:");
             for answer in incorrect_answers {
-                println!(self, "{} {}", INCORRECT, answer);
+                println!(self, "{} {}", INCORRECT, describe(answer));
             }
         }
 
         if !correct_answers.is_empty() {
             println!(self, "Correct answers:");
             for answer in correct_answers {
-                println!(self, "{} {}", CORRECT, answer);
+                println!(self, "{} {}", CORRECT, describe(answer));
             }
         }
 
         if !missing_answers.is_empty() {
             println!(self, "Missing answers:");
+            let response_language = prompt.question_kind.response_language();
             for answer in missing_answers {
-                println!(self, "{} {}", MISSING, answer);
+                println!(
+                    self,
+                    "{} {}",
+                    MISSING,
+                    romanized(prompt, response_language, answer)
+                );
             }
         }
 
         loop {
-            println!(self, "Did you know it (yes/almost/no)? ");
-            if let Some(r) = self.delegate.read_result(prompt)? {
+            match suggested {
+                Some(result) => {
+                    println!(
+                        self,
+                        "Did you know it (yes/almost/no, enter for {:?})? ", result
+                    );
+                }
+                None => {
+                    println!(self, "Did you know it (yes/almost/no)? ");
+                }
+            }
+            if let Some(r) = self.delegate.read_result(prompt, suggested)? {
                 return Ok(r);
             }
         }
@@ -124,3 +150,23 @@ impl<D: TextDelegate> Presentation for TextPresentation<D> {
         }
     }
 }
+
+/// Formats `text`, which is in `language`, along with its romanization
+/// if the user wants one.
+fn romanized(prompt: Prompt<'_>, language: Language, text: &str) -> String {
+    match prompt
+        .romanization
+        .and_then(|scheme| language.romanize(scheme, text))
+    {
+        Some(romanization) => format!("{} ({})", text, romanization),
+        None => text.to_string(),
+    }
+}
+
+/// Formats a response along with what was off about it, if anything.
+fn describe(answer: &GradedResponse) -> String {
+    match answer.feedback() {
+        Some(feedback) => format!("{} ({})", answer.text, feedback),
+        None => answer.text.clone(),
+    }
+}
@@ -15,12 +15,31 @@ async fn serve_cards(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::Endpo
     Ok(tide::response::json(uuids))
 }
 
+#[derive(Serialize)]
+struct CardResponse<'c> {
+    #[serde(flatten)]
+    card: &'c Card,
+
+    /// The romanization of each line, if the user asked for them and
+    /// the line is in a language that has one.
+    romanized: Vec<Option<String>>,
+}
+
 async fn serve_card(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
     let repo = cx.app_data().lock().unwrap();
     let uuid: Uuid = cx.param("uuid").map_err(|_| StatusCode::BAD_REQUEST)?;
     log::info!("serve_card uuid={}", uuid);
     let card = repo.card(uuid);
-    Ok(tide::response::json(card))
+    let romanization = repo.user_config().romanization;
+    let romanized = card
+        .lines
+        .iter()
+        .map(|line| match (&line.kind, romanization) {
+            (&LineKind::Meaning(language), Some(scheme)) => language.romanize(scheme, &line.text),
+            _ => None,
+        })
+        .collect();
+    Ok(tide::response::json(CardResponse { card, romanized }))
 }
 
 async fn quiz_cards(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
@@ -28,11 +47,10 @@ async fn quiz_cards(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::Endpoi
     let language: Language = cx.param("lang").map_err(|_| StatusCode::BAD_REQUEST)?;
     log::info!("quiz_cards language={:?}", language);
 
-    let suitable_questions = SUITABLE_QUESTIONS
-        .iter()
-        .filter_map(|(l, qks)| if *l == language { Some(*qks) } else { None })
-        .next()
-        .ok_or(StatusCode::BAD_REQUEST)?;
+    let suitable_questions = quiz::suitable_questions(language);
+    if suitable_questions.is_empty() {
+        return Err(StatusCode::BAD_REQUEST)?;
+    }
 
     let rng = &mut rand::thread_rng();
     let cards = selection::expired_cards(rng, &repo, &suitable_questions);
@@ -42,28 +60,117 @@ async fn quiz_cards(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::Endpoi
 
 async fn transliterate(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
     log::info!("transliterate");
+    let repo = cx.app_data().lock().unwrap();
     let language: Language = cx.param("lang").map_err(|_| StatusCode::BAD_REQUEST)?;
-    log::info!("transliterate={:?}", language);
+    let scheme = repo.user_config().transliteration(language);
+    log::info!("transliterate={:?} scheme={:?}", language, scheme);
 
     // FIXME(tide) -- this all looks like tide bugs to me
     let text: String = cx.param("text*").map_err(|_| StatusCode::BAD_REQUEST)?;
     let text: String = percent_encoding::percent_decode(text.as_bytes()).decode_utf8().map_err(|_| StatusCode::BAD_REQUEST)?.into_owned();
 
     log::info!("transliterate={:?}", text);
-    let out_text = language.transliterate(&text);
+    let out_text = language.transliterate_with(scheme, &text);
     log::info!("transliterate={:?}", out_text);
     Ok(tide::response::json(out_text))
 }
 
 async fn check_answer(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
     log::info!("check_answer");
+    let repo = cx.app_data().lock().unwrap();
+    let language: Language = cx.param("lang").map_err(|_| StatusCode::BAD_REQUEST)?;
     let expected: String = cx.param("expected").map_err(|_| StatusCode::BAD_REQUEST)?;
     let expected: String = percent_encoding::percent_decode(expected.as_bytes()).decode_utf8().map_err(|_| StatusCode::BAD_REQUEST)?.into_owned(); // TIDE bug?
     let user: String = cx.param("user").map_err(|_| StatusCode::BAD_REQUEST)?;
     let user: String = percent_encoding::percent_decode(user.as_bytes()).decode_utf8().map_err(|_| StatusCode::BAD_REQUEST)?.into_owned(); // TIDE bug?
-    let result = quiz::check_user_response(&expected, &user);
-    log::info!("expected={:?} user={:?} result={:?}", expected, user, result);
-    Ok(tide::response::json(result))
+    let syntax = repo.config().answer_syntax(language);
+    let mut grader = quiz::ResponseGrader::new(Some(&expected[..]), syntax, false);
+    let graded = grader.grade(user);
+    log::info!("expected={:?} user={:?} result={:?}", expected, graded.text, graded.response_match);
+    Ok(tide::response::json(CheckAnswerResult {
+        correct: graded.correct,
+        suggests_almost: graded.response_match.suggests_almost(),
+        feedback: graded.feedback(),
+    }))
+}
+
+#[derive(Serialize)]
+struct CheckAnswerResult {
+    correct: bool,
+
+    /// True if the answer was close, in which case the client should
+    /// suggest "almost" as the result.
+    suggests_almost: bool,
+
+    /// What was off about the answer, e.g. "did you mean `μάθημα`?".
+    feedback: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct GradeAnswersRequest {
+    uuid: Uuid,
+    question_kind: QuestionKind,
+
+    /// Every answer the user has given so far, in order.
+    answers: Vec<String>,
+
+    #[serde(default)]
+    strict: bool,
+}
+
+#[derive(Serialize)]
+struct GradeAnswersResponse<'g> {
+    answers: Vec<GradedAnswer<'g>>,
+    missing: &'g [&'g str],
+    suggested: Option<QuestionResult>,
+}
+
+#[derive(Serialize)]
+struct GradedAnswer<'g> {
+    text: &'g str,
+    correct: bool,
+    suggests_almost: bool,
+
+    /// The expected answer that it matched best, unless it was wrong.
+    expected: Option<&'g str>,
+
+    /// What was off about the answer, e.g. "did you mean `μάθημα`?".
+    feedback: Option<String>,
+}
+
+/// Grades the answers to a question exactly as `mathema quiz` would.
+async fn grade_answers(mut cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
+    log::info!("grade_answers");
+    let request: GradeAnswersRequest = cx.body_json().await.map_err(|_| StatusCode::BAD_REQUEST)?;
+    let repo = cx.app_data().lock().unwrap();
+    let card = repo.cards().get(&request.uuid).ok_or(StatusCode::NOT_FOUND)?;
+    let question_kind = request.question_kind;
+    let syntax = repo.config().answer_syntax(question_kind.response_language());
+    let mut grader = quiz::ResponseGrader::new(
+        card.lines_with_kind(question_kind.response_line_kind()),
+        syntax,
+        request.strict,
+    );
+    for answer in request.answers {
+        grader.grade(answer);
+    }
+    log::info!("grade_answers uuid={} suggested={:?}", request.uuid, grader.suggested_result());
+
+    Ok(tide::response::json(GradeAnswersResponse {
+        answers: grader
+            .responses()
+            .iter()
+            .map(|r| GradedAnswer {
+                text: &r.text,
+                correct: r.correct,
+                suggests_almost: r.response_match.suggests_almost(),
+                expected: r.expected.as_ref().map(|e| &e[..]),
+                feedback: r.feedback(),
+            })
+            .collect(),
+        missing: grader.missing(),
+        suggested: grader.suggested_result(),
+    }))
 }
 
 async fn mark_answer(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
@@ -76,14 +183,15 @@ async fn mark_answer(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::Endpo
     let question_kind = QuestionKind::Translate { from, to };
 
     let mut repo = cx.app_data().lock().unwrap();
-    let record = repo.database_mut().card_record_mut(uuid);
-    record.push_question_record(
+    repo.record_answer(
+        uuid,
         question_kind,
         QuestionRecord {
             date: Utc::now(),
             result: response,
         },
-    );
+    )
+    .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
 
     Ok(tide::response::json("ok"))
 }
@@ -91,7 +199,8 @@ async fn mark_answer(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::Endpo
 async fn write_db(cx: tide::Context<Mutex<MathemaRepository>>) -> tide::EndpointResult {
     log::info!("write_db");
     let mut repo = cx.app_data().lock().unwrap();
-    repo.write_database().map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let message = format!("serve: {}", repo.answers_summary());
+    repo.write_database(&message).map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
     Ok(tide::response::json("ok"))
 }
 
@@ -140,7 +249,8 @@ crate fn serve(options: &MathemaOptions) -> Fallible<()> {
         app.at("/api/card/:uuid").get(serve_card);
         app.at("/api/quiz_cards/:lang").get(quiz_cards);
         app.at("/api/transliterate/:lang/:text*").get(transliterate);
-        app.at("/api/check_answer/:expected/:user").get(check_answer);
+        app.at("/api/check_answer/:lang/:expected/:user").get(check_answer);
+        app.at("/api/grade_answers").post(grade_answers);
         app.at("/api/mark_answer/:uuid/translate/:from/:to/:response").post(mark_answer);
         app.at("/api/write_db").post(write_db);
 
@@ -0,0 +1,27 @@
+//! Keeping the deck in a plain directory: the files are all there is,
+//! so saving them needs no further work, and syncing is left to
+//! whatever manages the directory.
+
+use super::{Storage, SyncResult};
+use crate::prelude::*;
+
+crate struct DirectoryStorage;
+
+impl Storage for DirectoryStorage {
+    fn has_unsaved_answers(&self) -> Fallible<bool> {
+        Ok(false)
+    }
+
+    fn save(
+        &mut self,
+        _card_files: &[PathBuf],
+        _forgotten_card_files: &[PathBuf],
+        _message: &str,
+    ) -> Fallible<()> {
+        Ok(())
+    }
+
+    fn sync(&self) -> Fallible<SyncResult> {
+        throw!(MathemaErrorKind::SyncNeedsGit)
+    }
+}
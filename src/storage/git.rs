@@ -0,0 +1,492 @@
+//! Keeping the deck in a git repository: every `save` is a commit, and
+//! `sync` pulls from and pushes to a remote.
+
+use super::{Storage, SyncResult};
+use crate::config::RELATIVE_CONFIG_PATH;
+use crate::git::{RELATIVE_DB_PATH, RELATIVE_JOURNAL_PATH};
+use crate::prelude::*;
+use std::process;
+
+crate struct GitStorage {
+    directory_path: PathBuf,
+    repository: git2::Repository,
+}
+
+/// The git remote that `sync` pulls from and pushes to.
+crate const REMOTE_NAME: &str = "mathema";
+
+/// Where the stage of an index entry is kept in its flags. Stage 0 is
+/// a normal entry; the other stages are the three sides of a conflict.
+const INDEX_STAGE_MASK: u16 = 0x3000;
+const INDEX_STAGE_SHIFT: u16 = 12;
+
+const RELATIVE_GITATTRIBUTES_PATH: &str = ".gitattributes";
+
+/// Lines of the journal are never changed, only added, so two versions
+/// of it merge by taking both sets of lines. The database has a merge
+/// driver of its own (see `merge_db`).
+const GITATTRIBUTES: &str = "\
+.mathema-history/*.jsonl merge=union
+.mathema-v1.ron merge=mathema
+";
+
+/// How git should run the `mathema` merge driver named in `GITATTRIBUTES`.
+const MERGE_DRIVER: &str = "mathema merge-db %O %A %B";
+
+impl GitStorage {
+    /// Creates a git repository in the (new) deck directory.
+    crate fn init(directory_path: &Path) -> Fallible<GitStorage> {
+        let repository = git2::Repository::init(directory_path)?;
+
+        let gitattributes_path = directory_path.join(RELATIVE_GITATTRIBUTES_PATH);
+        fs::write(&gitattributes_path, GITATTRIBUTES).with_context(|_| {
+            MathemaErrorKind::AccessingFile {
+                file: gitattributes_path.display().to_string(),
+            }
+        })?;
+
+        let storage = GitStorage {
+            directory_path: directory_path.to_owned(),
+            repository,
+        };
+        storage.install_merge_driver()?;
+        Ok(storage)
+    }
+
+    crate fn open(directory_path: &Path, dry_run: bool) -> Fallible<GitStorage> {
+        let repository = git2::Repository::open(directory_path).with_context(|_| {
+            MathemaErrorKind::NoGitRepositoryFound {
+                directory_path: directory_path.display().to_string(),
+            }
+        })?;
+
+        let storage = GitStorage {
+            directory_path: directory_path.to_owned(),
+            repository,
+        };
+        if !dry_run {
+            // Clones don't get the configuration from `init`.
+            storage.install_merge_driver_if_missing()?;
+        }
+        Ok(storage)
+    }
+
+    /// Like `install_merge_driver`, but leaves `.git/config` alone if
+    /// git already knows a driver, whether ours or the user's own.
+    fn install_merge_driver_if_missing(&self) -> Fallible<()> {
+        let config = self.repository.config()?;
+        if config.get_entry("merge.mathema.driver").is_ok() {
+            return Ok(());
+        }
+        self.install_merge_driver()
+    }
+
+    /// Tells git how to run the merge driver for the database. This
+    /// lives in `.git/config`, which git doesn't copy when cloning.
+    fn install_merge_driver(&self) -> Fallible<()> {
+        let mut config = self.repository.config()?;
+        config.set_str("merge.mathema.name", "mathema database")?;
+        config.set_str("merge.mathema.driver", MERGE_DRIVER)?;
+        Ok(())
+    }
+
+    /// Who commits: the user, as configured for git (`user.name` and
+    /// `user.email`), or else mathema itself.
+    fn signature(&self) -> Fallible<git2::Signature<'static>> {
+        match self.repository.signature() {
+            Ok(signature) => Ok(signature),
+            Err(_) => Ok(git2::Signature::now("mathema", "mathema@example.com")?),
+        }
+    }
+
+    /// Creates a commit, like `git2::Repository::commit`, by the user
+    /// (see `signature`). If they asked git to sign their commits
+    /// (`commit.gpgsign`), it is signed.
+    fn commit(
+        &self,
+        update_ref: Option<&str>,
+        message: &str,
+        tree: &git2::Tree<'_>,
+        parents: &[&git2::Commit<'_>],
+    ) -> Fallible<git2::Oid> {
+        let signature = self.signature()?;
+        let config = self.repository.config()?;
+        if !config.get_bool("commit.gpgsign").unwrap_or(false) {
+            return Ok(self
+                .repository
+                .commit(update_ref, &signature, &signature, message, tree, parents)?);
+        }
+
+        let content = commit_content(&signature, message, tree, parents);
+        let commit_signature = sign(&config, &signature, &content)?;
+        let oid = self
+            .repository
+            .commit_signed(&content, &commit_signature, Some("gpgsig"))?;
+
+        if let Some(update_ref) = update_ref {
+            // `HEAD` names the current branch, which may not exist yet.
+            let reference = self.repository.find_reference(update_ref)?;
+            let name = reference
+                .symbolic_target()
+                .unwrap_or(update_ref)
+                .to_string();
+            self.repository.reference(&name, oid, true, message)?;
+        }
+        Ok(oid)
+    }
+
+    /// Merges the commit `their_oid` into `head_oid`, and commits the
+    /// result. The database and the journal merge as `merge_db` and
+    /// git's union merge would; a conflict in any other file (like a
+    /// card file) is an error, and leaves the repository as it was.
+    fn merge_from(&self, branch: &str, head_oid: git2::Oid, their_oid: git2::Oid) -> Fallible<()> {
+        let our_commit = self.repository.find_commit(head_oid)?;
+        let their_commit = self.repository.find_commit(their_oid)?;
+        let mut index = self
+            .repository
+            .merge_commits(&our_commit, &their_commit, None)?;
+
+        // The ancestor's, our and their versions of each conflicting file.
+        let mut conflicts: BTreeMap<PathBuf, [Option<git2::IndexEntry>; 3]> = BTreeMap::new();
+        for entry in index.iter() {
+            let stage = (entry.flags & INDEX_STAGE_MASK) >> INDEX_STAGE_SHIFT;
+            if stage > 0 {
+                let path = PathBuf::from(String::from_utf8_lossy(&entry.path).into_owned());
+                conflicts.entry(path).or_default()[stage as usize - 1] = Some(entry);
+            }
+        }
+
+        // Even if git could merge the database as text, the result may
+        // not be what we want, so we always merge it ourselves.
+        let db_path = Path::new(RELATIVE_DB_PATH);
+        let mut database = self.database_in(&our_commit)?;
+        database.merge(self.database_in(&their_commit)?);
+        index.add_frombuffer(
+            &Self::index_entry(db_path),
+            database.to_string_keeping_records()?.as_bytes(),
+        )?;
+        conflicts.remove(db_path);
+
+        let journal_conflicts: Vec<PathBuf> = conflicts
+            .keys()
+            .filter(|path| path.starts_with(RELATIVE_JOURNAL_PATH))
+            .cloned()
+            .collect();
+        for path in journal_conflicts {
+            // Skip the ancestor's version: the other two have its lines.
+            let sides = conflicts.remove(&path).unwrap();
+            let mut seen = HashSet::new();
+            let mut lines = String::new();
+            for entry in sides[1..].iter().filter_map(Option::as_ref) {
+                let blob = self.repository.find_blob(entry.id)?;
+                for line in String::from_utf8_lossy(blob.content()).lines() {
+                    if seen.insert(line.to_string()) {
+                        lines.push_str(line);
+                        lines.push('\n');
+                    }
+                }
+            }
+            index.add_frombuffer(&Self::index_entry(&path), lines.as_bytes())?;
+        }
+
+        if !conflicts.is_empty() {
+            throw!(MathemaErrorKind::SyncConflicts {
+                files: conflicts
+                    .keys()
+                    .map(|path| format!("`{}`", path.display()))
+                    .join(", "),
+                remote: REMOTE_NAME,
+                branch: branch.to_string(),
+            });
+        }
+
+        let tree_id = index.write_tree_to(&self.repository)?;
+        let tree = self.repository.find_tree(tree_id)?;
+        let merge_oid = self.commit(
+            None,
+            &format!("sync: merge changes from `{}`", REMOTE_NAME),
+            &tree,
+            &[&our_commit, &their_commit],
+        )?;
+        self.move_head_to(&self.repository.find_commit(merge_oid)?)?;
+
+        Ok(())
+    }
+
+    /// Checks out `commit`, and points the current branch at it.
+    fn move_head_to(&self, commit: &git2::Commit<'_>) -> Fallible<()> {
+        // Check out first: if that fails (because a file we need to
+        // change has local changes, say), the branch stays where it was.
+        self.repository.checkout_tree(commit.as_object(), None)?;
+        self.repository
+            .head()?
+            .set_target(commit.id(), "mathema: sync")?;
+        Ok(())
+    }
+
+    /// Reads the database as it was in `commit`.
+    fn database_in(&self, commit: &git2::Commit<'_>) -> Fallible<Database> {
+        let entry = commit.tree()?.get_path(Path::new(RELATIVE_DB_PATH))?;
+        let blob = self.repository.find_blob(entry.id())?;
+        Database::load_from(blob.content())
+    }
+
+    /// An index entry for the regular file `path`, for `add_frombuffer`
+    /// to fill in.
+    fn index_entry(path: &Path) -> git2::IndexEntry {
+        git2::IndexEntry {
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: 0o100_644,
+            uid: 0,
+            gid: 0,
+            file_size: 0,
+            id: git2::Oid::zero(),
+            flags: 0,
+            flags_extended: 0,
+            path: path.to_string_lossy().into_owned().into_bytes(),
+        }
+    }
+
+    fn push(&self, remote: &mut git2::Remote<'_>, branch: &str) -> Fallible<()> {
+        let mut rejection = None;
+        {
+            let mut callbacks = Self::remote_callbacks();
+            callbacks.push_update_reference(|_, status| {
+                rejection = status.map(|status| status.to_string());
+                Ok(())
+            });
+            let mut push_options = git2::PushOptions::new();
+            push_options.remote_callbacks(callbacks);
+            remote.push(
+                &[&format!("refs/heads/{0}:refs/heads/{0}", branch)],
+                Some(&mut push_options),
+            )?;
+        }
+
+        if let Some(message) = rejection {
+            throw!(MathemaErrorKind::PushRejected {
+                remote: REMOTE_NAME,
+                message,
+            });
+        }
+        Ok(())
+    }
+
+    /// Callbacks for talking to the remote. For SSH, we ask the SSH
+    /// agent for keys.
+    fn remote_callbacks<'a>() -> git2::RemoteCallbacks<'a> {
+        let mut callbacks = git2::RemoteCallbacks::new();
+        callbacks.credentials(|_url, username, allowed| {
+            if allowed.contains(git2::CredentialType::SSH_KEY) {
+                git2::Cred::ssh_key_from_agent(username.unwrap_or("git"))
+            } else {
+                git2::Cred::default()
+            }
+        });
+        callbacks
+    }
+}
+
+impl Storage for GitStorage {
+    /// True if the journal has answers that have not been committed.
+    fn has_unsaved_answers(&self) -> Fallible<bool> {
+        let mut options = git2::StatusOptions::new();
+        options
+            .pathspec(RELATIVE_JOURNAL_PATH)
+            .include_untracked(true)
+            .recurse_untracked_dirs(true);
+        Ok(!self.repository.statuses(Some(&mut options))?.is_empty())
+    }
+
+    /// Creates a new git commit, adding in the changes from all of
+    /// the registered `cards` files as well as the index.
+    fn save(
+        &mut self,
+        card_files: &[PathBuf],
+        forgotten_card_files: &[PathBuf],
+        message: &str,
+    ) -> Fallible<()> {
+        let mut index = self.repository.index()?;
+        index.add_path(Path::new(RELATIVE_DB_PATH))?;
+        if self.directory_path.join(RELATIVE_CONFIG_PATH).exists() {
+            index.add_path(Path::new(RELATIVE_CONFIG_PATH))?;
+        }
+        if self
+            .directory_path
+            .join(RELATIVE_GITATTRIBUTES_PATH)
+            .exists()
+        {
+            index.add_path(Path::new(RELATIVE_GITATTRIBUTES_PATH))?;
+        }
+        index.add_all(
+            &[RELATIVE_JOURNAL_PATH],
+            git2::IndexAddOption::DEFAULT,
+            None,
+        )?;
+        for card_file in card_files {
+            index.add_path(card_file)?;
+        }
+        for card_file in forgotten_card_files {
+            index.remove_path(card_file)?;
+        }
+        index.write()?;
+
+        let tree_id = self.repository.index()?.write_tree()?;
+        let tree = self.repository.find_tree(tree_id)?;
+        let parents = match self.repository.head() {
+            Ok(head_ref) => {
+                let head_oid = head_ref.target().unwrap();
+                Some(self.repository.find_commit(head_oid)?)
+            }
+            _ => None,
+        };
+        let parents: Vec<_> = parents.iter().collect();
+        self.commit(Some("HEAD"), message, &tree, &parents)?;
+        self.repository.checkout_head(None)?;
+
+        Ok(())
+    }
+
+    /// Brings the repository in sync with the `mathema` remote: fetches
+    /// from it, merges what we fetched, and pushes the result. Only
+    /// what has been committed is synced.
+    fn sync(&self) -> Fallible<SyncResult> {
+        let mut remote = self.repository.find_remote(REMOTE_NAME).with_context(|_| {
+            MathemaErrorKind::NoRemote {
+                remote: REMOTE_NAME,
+            }
+        })?;
+
+        let head = self.repository.head()?;
+        let branch = match head.shorthand() {
+            Some(branch) if head.is_branch() => branch.to_string(),
+            _ => throw!(MathemaErrorKind::NotOnBranch),
+        };
+        let head_oid = head.target().unwrap();
+
+        let tracking_ref = format!("refs/remotes/{}/{}", REMOTE_NAME, branch);
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(Self::remote_callbacks());
+        remote.fetch(
+            &[&format!("refs/heads/{}:{}", branch, tracking_ref)],
+            Some(&mut fetch_options),
+            None,
+        )?;
+
+        // If the remote doesn't have our branch yet, we just push it.
+        let result = match self.repository.refname_to_id(&tracking_ref) {
+            Err(_) => SyncResult::Pushed,
+            Ok(their_oid) if their_oid == head_oid => return Ok(SyncResult::UpToDate),
+            Ok(their_oid) => {
+                let base_oid = self.repository.merge_base(head_oid, their_oid)?;
+                if base_oid == their_oid {
+                    SyncResult::Pushed
+                } else if base_oid == head_oid {
+                    let their_commit = self.repository.find_commit(their_oid)?;
+                    self.move_head_to(&their_commit)?;
+                    return Ok(SyncResult::Pulled);
+                } else {
+                    self.merge_from(&branch, head_oid, their_oid)?;
+                    SyncResult::Merged
+                }
+            }
+        };
+
+        self.push(&mut remote, &branch)?;
+        Ok(result)
+    }
+}
+
+/// The content of a commit object, which is what gets signed.
+fn commit_content(
+    signature: &git2::Signature<'_>,
+    message: &str,
+    tree: &git2::Tree<'_>,
+    parents: &[&git2::Commit<'_>],
+) -> String {
+    let when = signature.when();
+    let offset = when.offset_minutes();
+    let ident = format!(
+        "{} <{}> {} {}{:02}{:02}",
+        signature.name().unwrap_or(""),
+        signature.email().unwrap_or(""),
+        when.seconds(),
+        if offset < 0 { '-' } else { '+' },
+        offset.abs() / 60,
+        offset.abs() % 60,
+    );
+
+    let mut content = format!("tree {}\n", tree.id());
+    for parent in parents {
+        content.push_str(&format!("parent {}\n", parent.id()));
+    }
+    content.push_str(&format!(
+        "author {}\ncommitter {}\n\n{}",
+        ident, ident, message
+    ));
+    content
+}
+
+/// Signs `content` the way git would: with GPG, or with SSH if
+/// `gpg.format` says so.
+fn sign(config: &git2::Config, signature: &git2::Signature<'_>, content: &str) -> Fallible<String> {
+    let format = config.get_string("gpg.format").unwrap_or_default();
+    let signing_key = config.get_string("user.signingkey").ok();
+    let (program, args) = match &format[..] {
+        "ssh" => {
+            let signing_key = match signing_key {
+                Some(signing_key) => signing_key,
+                None => throw!(MathemaErrorKind::NoSigningKey),
+            };
+            let program = config
+                .get_string("gpg.ssh.program")
+                .unwrap_or_else(|_| "ssh-keygen".to_string());
+            let args = vec![
+                "-Y".to_string(),
+                "sign".to_string(),
+                "-n".to_string(),
+                "git".to_string(),
+                "-f".to_string(),
+                signing_key,
+            ];
+            (program, args)
+        }
+        _ => {
+            // Without a key, GPG picks one by the committer's identity.
+            let signing_key = signing_key.unwrap_or_else(|| signature.to_string());
+            let program = config
+                .get_string("gpg.program")
+                .unwrap_or_else(|_| "gpg".to_string());
+            let args = vec!["-bsau".to_string(), signing_key];
+            (program, args)
+        }
+    };
+
+    let output: io::Result<process::Output> = try {
+        let mut child = process::Command::new(&program)
+            .args(&args)
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn()?;
+        child.stdin.take().unwrap().write_all(content.as_bytes())?;
+        child.wait_with_output()?
+    };
+    let output = match output {
+        Ok(output) => output,
+        Err(error) => throw!(MathemaErrorKind::SigningFailed {
+            program,
+            message: error.to_string(),
+        }),
+    };
+    if !output.status.success() {
+        throw!(MathemaErrorKind::SigningFailed {
+            program,
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
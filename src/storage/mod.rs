@@ -0,0 +1,65 @@
+//! Where a deck's history is kept. The deck's files are always plain
+//! files in its directory; a `Storage` decides what happens once they
+//! have been written. By default that's a commit in the deck's git
+//! repository, but decks that live in a directory synced by some other
+//! tool (or inside a bigger repository) can opt out of git with
+//! `storage: Directory` in their configuration.
+
+use crate::prelude::*;
+
+crate mod directory;
+crate mod git;
+
+crate use self::directory::DirectoryStorage;
+crate use self::git::{GitStorage, REMOTE_NAME};
+
+crate trait Storage: Send {
+    /// True if the journal has answers that were never saved, because
+    /// the quiz that recorded them was interrupted.
+    fn has_unsaved_answers(&self) -> Fallible<bool>;
+
+    /// Saves the deck, whose files (the database, the journal, the
+    /// configuration and `card_files`) have just been written, and
+    /// from which `forgotten_card_files` have been deleted. `message`
+    /// says what changed.
+    fn save(
+        &mut self,
+        card_files: &[PathBuf],
+        forgotten_card_files: &[PathBuf],
+        message: &str,
+    ) -> Fallible<()>;
+
+    /// Brings the deck in sync with its copies elsewhere.
+    fn sync(&self) -> Fallible<SyncResult>;
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+crate enum StorageKind {
+    /// A git repository (see `GitStorage`).
+    Git,
+
+    /// Just the directory (see `DirectoryStorage`).
+    Directory,
+}
+
+impl Default for StorageKind {
+    fn default() -> Self {
+        StorageKind::Git
+    }
+}
+
+/// What `sync` did.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+crate enum SyncResult {
+    /// Nothing: we and the remote were the same already.
+    UpToDate,
+
+    /// Only the remote had changes, and we took them.
+    Pulled,
+
+    /// Only we had changes, and we pushed them.
+    Pushed,
+
+    /// Both had changes; we merged them and pushed the result.
+    Merged,
+}
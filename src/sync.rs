@@ -0,0 +1,30 @@
+//! Sync subcommand: pulls from, merges with and pushes to the `mathema`
+//! remote.
+
+use crate::storage::{SyncResult, REMOTE_NAME};
+use crate::prelude::*;
+
+crate fn sync(options: &MathemaOptions) -> Fallible<()> {
+    if options.dry_run {
+        throw!(MathemaErrorKind::IncompatibleOption {
+            option: "--dry-run",
+            command: "sync",
+        });
+    }
+
+    let repo = MathemaRepository::open(options)?;
+    report(repo.sync()?);
+    Ok(())
+}
+
+crate fn report(result: SyncResult) {
+    match result {
+        SyncResult::UpToDate => println!("Already in sync with `{}`.", REMOTE_NAME),
+        SyncResult::Pulled => println!("Pulled changes from `{}`.", REMOTE_NAME),
+        SyncResult::Pushed => println!("Pushed changes to `{}`.", REMOTE_NAME),
+        SyncResult::Merged => println!(
+            "Merged changes from `{}` and pushed the result.",
+            REMOTE_NAME
+        ),
+    }
+}
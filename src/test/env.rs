@@ -17,7 +17,7 @@ use self::assert_cli::Assert;
 use self::tempdir::TempDir;
 use std::fs::{File, OpenOptions};
 use std::io::{self, prelude::*};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
 macro_rules! mathema_test {
     ($test_name:ident is $closure:expr) => {
@@ -60,6 +60,10 @@ impl TestEnv {
         Assert::command(&["git"]).current_dir(self.temp_dir.path().join(in_dir).to_owned())
     }
 
+    crate fn path(&self, name: &str) -> PathBuf {
+        self.temp_dir.path().join(name)
+    }
+
     crate fn write_file(&mut self, name: &str, contents: &str) -> io::Result<()> {
         let path = self.temp_dir.path().join(name);
         let mut file = File::create(path)?;
@@ -85,6 +85,11 @@ gr γιάσου
            .with_args(&["show", "--stat", "HEAD"])
            .stdout().contains(" bar.cards       | 3 +++")
            .unwrap();
+
+        env.assert_git("foo")
+           .with_args(&["log", "-1", "--format=%s"])
+           .stdout().contains("add: bar.cards (1 new card)")
+           .unwrap();
     }
 }
 
@@ -185,3 +190,595 @@ gr γιάσου
         assert_eq!(num_uuids, 1, "too many uuids in `{}`", string);
     }
 }
+
+mathema_test! {
+    export_import_json is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        env.write_file("foo/bar.cards", "\
+# greetings
+en hello
+gr γιάσου
+")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["add", "bar.cards"])
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["export", "--format", "json", "-o", "../deck.json"])
+           .unwrap();
+
+        env.assert_mathema("")
+           .with_args(&["import", "deck.json", "baz"])
+           .stdout()
+           .contains("Imported 1 card files")
+           .unwrap();
+
+        assert_eq!(
+            env.read_file("foo/bar.cards").unwrap(),
+            env.read_file("baz/bar.cards").unwrap(),
+        );
+
+        env.assert_git("baz")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+
+        // An existing directory is left alone.
+        env.assert_mathema("")
+           .with_args(&["import", "deck.json", "foo"])
+           .stderr().contains("`foo` already exists; import into a new directory")
+           .and()
+           .fails()
+           .unwrap();
+        assert!(env.path("foo/bar.cards").exists());
+    }
+}
+
+mathema_test! {
+    export_import_custom_language is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        env.write_file("foo/mathema-config.ron", "\
+(
+    articles: { Ukrainian: Optional },
+    languages: [
+        (
+            id: \"Ukrainian\",
+            code: \"uk\",
+            name: \"Українська\",
+        ),
+    ],
+)
+")
+           .unwrap();
+
+        env.write_file("foo/bar.cards", "\
+# greetings
+en hello
+uk привіт
+")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["add", "bar.cards"])
+           .unwrap();
+
+        // An answer in the deck's own language.
+        std::fs::create_dir(env.path("foo/.mathema-history")).unwrap();
+        env.write_file("foo/.mathema-history/2019-05.jsonl", "\
+{\"uuid\":\"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\",\"question_kind\":{\"Translate\":{\"from\":\"Ukrainian\",\"to\":\"English\"}},\"date\":\"2019-05-01T12:00:00Z\",\"result\":\"Almost\"}
+")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["export", "--format", "json", "-o", "../deck.json"])
+           .unwrap();
+
+        env.assert_mathema("")
+           .with_args(&["import", "deck.json", "baz"])
+           .stdout()
+           .contains("Imported 1 card files")
+           .unwrap();
+
+        assert_eq!(
+            env.read_file("foo/bar.cards").unwrap(),
+            env.read_file("baz/bar.cards").unwrap(),
+        );
+        let config = env.read_file("baz/mathema-config.ron").unwrap();
+        assert!(config.contains("Ukrainian: Optional"));
+        assert!(config.contains("code: \"uk\""));
+
+        env.assert_mathema("baz")
+           .with_args(&["history", "--csv"])
+           .stdout()
+           .contains("6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d")
+           .unwrap();
+
+        env.assert_git("baz")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    recover_interrupted_quiz is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        // What a quiz leaves behind if it is killed before it commits.
+        std::fs::create_dir(env.path("foo/.mathema-history")).unwrap();
+        env.write_file("foo/.mathema-history/2019-05.jsonl", "\
+{\"uuid\":\"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\",\"question_kind\":{\"Translate\":{\"from\":\"Greek\",\"to\":\"English\"}},\"date\":\"2019-05-01T12:00:00Z\",\"result\":\"Almost\"}
+")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["history", "--csv"])
+           .stderr()
+           .contains("Committing answers from a quiz that was interrupted.")
+           .and()
+           .stdout()
+           .contains("6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d")
+           .unwrap();
+
+        env.assert_git("foo")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    move_records_into_journal is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        // Databases used to hold the records themselves.
+        env.write_file("foo/.mathema-v1.ron", "\
+(
+    card_files: [],
+    user: (
+        records: {
+            \"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\": (
+                questions: {
+                    Translate(from: Greek, to: English): [
+                        (date: \"2019-05-01T12:00:00Z\", result: Almost),
+                    ],
+                },
+            ),
+        },
+    ),
+)
+")
+           .unwrap();
+
+        // A dry run sees the old records, but leaves them where they are.
+        env.assert_mathema("foo")
+           .with_args(&["--dry-run", "history", "--csv"])
+           .stdout()
+           .contains("6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d")
+           .unwrap();
+        assert!(!env.path("foo/.mathema-history").exists());
+
+        env.assert_mathema("foo")
+           .with_args(&["history", "--csv"])
+           .stderr()
+           .contains("Moved 1 answers from the database into `.mathema-history`.")
+           .and()
+           .stdout()
+           .contains("6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d")
+           .unwrap();
+
+        let journal = env.read_file("foo/.mathema-history/2019-05.jsonl").unwrap();
+        assert!(journal.contains("6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d"), "journal is `{}`", journal);
+        let database = env.read_file("foo/.mathema-v1.ron").unwrap();
+        assert!(!database.contains("records"), "database is `{}`", database);
+
+        env.assert_git("foo")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    upgrade_database is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+        assert!(env.read_file("foo/.mathema-v1.ron").unwrap().contains("version: 2"));
+
+        // Databases from before the format had versions.
+        env.write_file("foo/.mathema-v1.ron", "(card_files: [])").unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["--dry-run", "status"])
+           .unwrap();
+        assert!(!env.path("foo/.mathema-v1.ron.v1.bak").exists());
+
+        env.assert_mathema("foo")
+           .with_args(&["status"])
+           .stderr().contains("Upgraded the database from version 1 to version 2 (the old one is in git's history).")
+           .unwrap();
+        assert!(!env.path("foo/.mathema-v1.ron.v1.bak").exists());
+        assert!(env.read_file("foo/.mathema-v1.ron").unwrap().contains("version: 2"));
+        env.assert_git("foo")
+           .with_args(&["log", "-1", "--format=%s"])
+           .stdout().contains("migrate: upgrade the database from version 1 to version 2")
+           .unwrap();
+
+        // Without git, we keep a copy ourselves.
+        env.assert_mathema("")
+           .with_args(&["new", "--no-git", "bar"])
+           .unwrap();
+        env.write_file("bar/.mathema-v1.ron", "(card_files: [])").unwrap();
+        env.assert_mathema("bar")
+           .with_args(&["status"])
+           .stderr().contains("Upgraded the database from version 1 to version 2 (the old one is in `.mathema-v1.ron.v1.bak`).")
+           .unwrap();
+        assert_eq!(env.read_file("bar/.mathema-v1.ron.v1.bak").unwrap(), "(card_files: [])");
+
+        env.write_file("foo/.mathema-v1.ron", "(version: 3, card_files: [])").unwrap();
+        env.assert_mathema("foo")
+           .with_args(&["status"])
+           .stderr().contains("The database was written by a newer mathema (format version 3, but this one only reads up to version 2)")
+           .and()
+           .fails()
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    merge_db_driver is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        assert!(env.read_file("foo/.gitattributes").unwrap().contains(".mathema-v1.ron merge=mathema"));
+        env.assert_git("foo")
+           .with_args(&["config", "merge.mathema.driver"])
+           .stdout().contains("mathema merge-db %O %A %B")
+           .unwrap();
+
+        // Opening the deck doesn't touch a driver that is there already.
+        env.assert_git("foo")
+           .with_args(&["config", "merge.mathema.driver", "my-merge %O %A %B"])
+           .unwrap();
+        env.assert_mathema("foo")
+           .with_args(&["status"])
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["config", "merge.mathema.driver"])
+           .stdout().contains("my-merge %O %A %B")
+           .unwrap();
+
+        env.write_file("foo/base.ron", "(card_files: [\"a.cards\"])").unwrap();
+        env.write_file("foo/ours.ron", "(card_files: [\"a.cards\", \"b.cards\"])").unwrap();
+        env.write_file("foo/theirs.ron", "(card_files: [\"a.cards\", \"c.cards\"])").unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["merge-db", "base.ron", "ours.ron", "theirs.ron"])
+           .unwrap();
+
+        let merged = env.read_file("foo/ours.ron").unwrap();
+        assert!(merged.contains("\"b.cards\""), "merged is `{}`", merged);
+        assert!(merged.contains("\"c.cards\""), "merged is `{}`", merged);
+    }
+}
+
+mathema_test! {
+    sync_with_remote is |env| {
+        env.assert_git("")
+           .with_args(&["init", "--bare", "remote.git"])
+           .unwrap();
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["remote", "add", "mathema", "../remote.git"])
+           .unwrap();
+
+        env.write_file("foo/a.cards", "en hello\ngr γιάσου\n").unwrap();
+        env.assert_mathema("foo").with_args(&["add", "a.cards"]).unwrap();
+        env.assert_mathema("foo")
+           .with_args(&["sync"])
+           .stdout().contains("Pushed changes to `mathema`.")
+           .unwrap();
+
+        env.assert_git("")
+           .with_args(&["clone", "-o", "mathema", "remote.git", "bar"])
+           .unwrap();
+        env.write_file("bar/b.cards", "en water\ngr νερό\n").unwrap();
+        env.assert_mathema("bar").with_args(&["add", "b.cards"]).unwrap();
+        env.assert_git("bar")
+           .with_args(&["config", "merge.mathema.driver"])
+           .stdout().contains("mathema merge-db %O %A %B")
+           .unwrap();
+        env.assert_mathema("bar")
+           .with_args(&["sync"])
+           .stdout().contains("Pushed changes to `mathema`.")
+           .unwrap();
+
+        // Both sides add a card file, so the database has to be merged.
+        env.write_file("foo/c.cards", "en yes\ngr ναι\n").unwrap();
+        env.assert_mathema("foo").with_args(&["add", "c.cards"]).unwrap();
+        env.assert_mathema("foo")
+           .with_args(&["sync"])
+           .stdout().contains("Merged changes from `mathema` and pushed the result.")
+           .unwrap();
+
+        let database = env.read_file("foo/.mathema-v1.ron").unwrap();
+        for card_file in &["a.cards", "b.cards", "c.cards"] {
+            assert!(database.contains(card_file), "database is `{}`", database);
+        }
+        assert!(env.read_file("foo/b.cards").unwrap().contains("νερό"));
+
+        env.assert_mathema("bar")
+           .with_args(&["sync"])
+           .stdout().contains("Pulled changes from `mathema`.")
+           .unwrap();
+        env.assert_mathema("bar")
+           .with_args(&["sync"])
+           .stdout().contains("Already in sync with `mathema`.")
+           .unwrap();
+        assert!(env.read_file("bar/c.cards").unwrap().contains("ναι"));
+    }
+}
+
+mathema_test! {
+    sync_card_file_conflict is |env| {
+        env.assert_git("")
+           .with_args(&["init", "--bare", "remote.git"])
+           .unwrap();
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["remote", "add", "mathema", "../remote.git"])
+           .unwrap();
+        env.write_file("foo/a.cards", "en hello\ngr γιάσου\n").unwrap();
+        env.assert_mathema("foo").with_args(&["add", "a.cards"]).unwrap();
+        env.assert_mathema("foo").with_args(&["sync"]).unwrap();
+
+        env.assert_git("")
+           .with_args(&["clone", "-o", "mathema", "remote.git", "bar"])
+           .unwrap();
+        env.append_file("bar/a.cards", "\nen water\ngr νερό\n").unwrap();
+        env.assert_git("bar")
+           .with_args(&["-c", "user.name=bar", "-c", "user.email=bar@example.com",
+                        "commit", "-am", "water"])
+           .unwrap();
+        env.assert_git("bar")
+           .with_args(&["push", "mathema", "master"])
+           .unwrap();
+
+        env.append_file("foo/a.cards", "\nen yes\ngr ναι\n").unwrap();
+        env.assert_git("foo")
+           .with_args(&["-c", "user.name=foo", "-c", "user.email=foo@example.com",
+                        "commit", "-am", "yes"])
+           .unwrap();
+        env.assert_mathema("foo")
+           .with_args(&["sync"])
+           .stderr().contains("`a.cards` changed both here and in `mathema`")
+           .and()
+           .fails()
+           .unwrap();
+
+        // Nothing was changed.
+        assert!(!env.read_file("foo/a.cards").unwrap().contains("νερό"));
+        env.assert_git("foo")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    commits_use_git_identity is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["config", "user.name", "Ada Lovelace"])
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["config", "user.email", "ada@example.com"])
+           .unwrap();
+
+        env.write_file("foo/bar.cards", "en hello\ngr γιάσου\n").unwrap();
+        env.assert_mathema("foo").with_args(&["add", "bar.cards"]).unwrap();
+
+        env.assert_git("foo")
+           .with_args(&["log", "-1", "--format=%an <%ae>"])
+           .stdout().contains("Ada Lovelace <ada@example.com>")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    signed_commits is |env| {
+        use std::os::unix::fs::PermissionsExt;
+
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        // Stands in for `gpg -bsau <key>`.
+        env.write_file("fake-gpg", "\
+#!/bin/sh
+cat > /dev/null
+echo '-----BEGIN PGP SIGNATURE-----'
+echo 'c2lnbmVk'
+echo '-----END PGP SIGNATURE-----'
+")
+           .unwrap();
+        let fake_gpg = env.path("fake-gpg");
+        std::fs::set_permissions(&fake_gpg, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        for &(key, value) in &[
+            ("user.name", "Ada Lovelace"),
+            ("user.email", "ada@example.com"),
+            ("commit.gpgsign", "true"),
+            ("gpg.program", fake_gpg.to_str().unwrap()),
+        ] {
+            env.assert_git("foo").with_args(&["config", key, value]).unwrap();
+        }
+
+        env.write_file("foo/bar.cards", "en hello\ngr γιάσου\n").unwrap();
+        env.assert_mathema("foo").with_args(&["add", "bar.cards"]).unwrap();
+
+        env.assert_git("foo")
+           .with_args(&["cat-file", "-p", "HEAD"])
+           .stdout().contains("gpgsig -----BEGIN PGP SIGNATURE-----")
+           .and()
+           .stdout().contains("add: bar.cards (1 new card)")
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    directory_storage is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "--no-git", "foo"])
+           .unwrap();
+        assert!(!env.path("foo/.git").exists());
+        assert!(env.read_file("foo/mathema-config.ron").unwrap().contains("storage: Directory"));
+
+        env.write_file("foo/bar.cards", "en hello\ngr γιάσου\n").unwrap();
+        env.assert_mathema("foo")
+           .with_args(&["add", "bar.cards"])
+           .stdout().contains("1 new card found.")
+           .unwrap();
+        assert!(env.read_file("foo/.mathema-v1.ron").unwrap().contains("bar.cards"));
+
+        env.assert_mathema("foo")
+           .with_args(&["sync"])
+           .stderr().contains("Only decks kept in git can be synced")
+           .and()
+           .fails()
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    fsck_and_repair is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        env.write_file("foo/bar.cards", "\
+en hello
+gr γιάσου
+
+en goodbye
+gr αντίο
+")
+           .unwrap();
+        env.write_file("foo/gone.cards", "en yes\ngr ναι\n").unwrap();
+        env.assert_mathema("foo").with_args(&["add", "bar.cards"]).unwrap();
+        env.assert_mathema("foo").with_args(&["add", "gone.cards"]).unwrap();
+        std::fs::remove_file(env.path("foo/gone.cards")).unwrap();
+
+        // A copy of the first card, UUID and all.
+        let bar = env.read_file("foo/bar.cards").unwrap();
+        let uuid = bar.lines()
+                      .find(|line| line.starts_with("uuid "))
+                      .unwrap()["uuid ".len()..]
+                      .to_string();
+        env.write_file("foo/baz.cards", &format!("en hi\ngr γεια\nuuid {}\n", uuid)).unwrap();
+        env.assert_mathema("foo").with_args(&["-f", "add", "baz.cards"]).unwrap();
+
+        // An answer to a card that doesn't exist, and one to a question
+        // we never ask.
+        std::fs::create_dir(env.path("foo/.mathema-history")).unwrap();
+        env.write_file("foo/.mathema-history/2019-05.jsonl", &format!("\
+{{\"uuid\":\"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\",\"question_kind\":{{\"Translate\":{{\"from\":\"Greek\",\"to\":\"English\"}}}},\"date\":\"2019-05-01T12:00:00Z\",\"result\":\"Almost\"}}
+{{\"uuid\":\"{}\",\"question_kind\":{{\"Translate\":{{\"from\":\"English\",\"to\":\"English\"}}}},\"date\":\"2019-05-02T12:00:00Z\",\"result\":\"Yes\"}}
+", uuid))
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["fsck"])
+           .stdout().contains("Card files that no longer exist:\n  gone.cards")
+           .and()
+           .stdout().contains("Answers to cards that no longer exist:\n  6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d (1 answer)")
+           .and()
+           .stdout().contains("Cards that share a UUID:")
+           .and()
+           .stdout().contains("Answers to questions that mathema no longer asks:")
+           .and()
+           .stdout().contains("Run `mathema fsck --repair` to fix these.")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["fsck", "--repair"])
+           .stdout().contains("Repaired the deck: renumber 1 card, forget 1 missing card file, delete 1 answer to deleted cards, delete 1 answer to questions no longer asked.")
+           .unwrap();
+
+        assert_eq!(env.read_file("foo/bar.cards").unwrap(), bar);
+        assert!(!env.read_file("foo/baz.cards").unwrap().contains(&uuid));
+        // The answers are still in the journal, but deleted.
+        let journal = std::fs::read_dir(env.path("foo/.mathema-history"))
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<String>();
+        assert!(journal.contains("{\"forget\":{\"uuid\":\"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\""), "journal is `{}`", journal);
+
+        env.assert_mathema("foo")
+           .with_args(&["fsck"])
+           .stdout().contains("No problems found.")
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["status"])
+           .stdout().contains("nothing to commit, working directory clean")
+           .unwrap();
+        env.assert_git("foo")
+           .with_args(&["log", "-1", "--format=%s"])
+           .stdout().contains("fsck: renumber 1 card")
+           .unwrap();
+    }
+}
+
+mathema_test! {
+    fsck_undefined_language is |env| {
+        env.assert_mathema("")
+           .with_args(&["new", "foo"])
+           .unwrap();
+
+        // An answer from when the deck defined Klingon.
+        std::fs::create_dir(env.path("foo/.mathema-history")).unwrap();
+        env.write_file("foo/.mathema-history/2019-05.jsonl", "\
+{\"uuid\":\"6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d\",\"question_kind\":{\"Translate\":{\"from\":\"Klingon\",\"to\":\"English\"}},\"date\":\"2019-05-01T12:00:00Z\",\"result\":\"Almost\"}
+")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["fsck"])
+           .stdout().contains("Answers to questions in languages that the deck doesn't define:\n  6f0e6c8a-52c1-4d8a-9d6c-3b0c0c1f6e1d ({\"Translate\":{\"from\":\"Klingon\",\"to\":\"English\"}}, 1 answer)")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["fsck", "--repair"])
+           .stdout().contains("Repaired the deck: delete 1 answer in undefined languages.")
+           .unwrap();
+
+        env.assert_mathema("foo")
+           .with_args(&["fsck"])
+           .stdout().contains("No problems found.")
+           .unwrap();
+    }
+}